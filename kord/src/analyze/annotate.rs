@@ -0,0 +1,58 @@
+//! Annotated analysis of audio in a known key.
+//!
+//! Detects the notes of a clip, and annotates each of them with its scale degree, and whether it is a chord tone.
+
+use crate::core::{
+    base::Res,
+    chord::Chord,
+    scale::{AnnotatedNote, Scale},
+};
+
+use super::base::get_notes_from_audio_data;
+
+// Functions.
+
+/// Gets the notes from audio data, annotated against the given key (e.g., for practice feedback like "you played the 7th").
+///
+/// Returns the diatonic chord that best covers the detected notes (or `None` if no notes were detected), along with the annotated notes.
+pub fn get_annotated_notes_from_audio_data(data: &[f32], length_in_seconds: f32, key: &Scale) -> Res<(Option<Chord>, Vec<AnnotatedNote>)> {
+    let notes = get_notes_from_audio_data(data, length_in_seconds)?;
+
+    let Some(chord) = key.best_diatonic_chord(&notes) else {
+        return Ok((None, Vec::new()));
+    };
+
+    let annotated = key.annotate(&notes, &chord);
+
+    Ok((Some(chord), annotated))
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{
+        chord::Chordable,
+        helpers::generate_tone,
+        note::{A, C, E, G},
+        pitch::HasFrequency,
+        scale::{ScaleKind, ToneRole},
+    };
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_annotated_notes() {
+        // An A minor triad with a passing G, in C major.
+        let data = generate_tone(&[A.frequency(), C.frequency() * 2.0, E.frequency() * 2.0, G.frequency() * 2.0], 1.0, 44_100);
+
+        let (chord, annotated) = get_annotated_notes_from_audio_data(&data, 1.0, &Scale::new(C, ScaleKind::Major)).unwrap();
+
+        assert_eq!(chord, Some(Chord::new(A).minor()));
+        assert_eq!(annotated.iter().map(|a| a.degree).collect::<Vec<_>>(), vec![Some(6), Some(1), Some(3), Some(5)]);
+        assert_eq!(
+            annotated.iter().map(|a| a.role).collect::<Vec<_>>(),
+            vec![ToneRole::ChordTone, ToneRole::ChordTone, ToneRole::ChordTone, ToneRole::PassingTone]
+        );
+    }
+}
@@ -2,7 +2,11 @@
 //!
 //! Performs ffts, frequency space smoothing, peak detection, harmonic collapsing, and note detection.
 
-use std::{collections::HashMap, ops::Deref};
+use std::{
+    collections::HashMap,
+    ops::Deref,
+    time::{Duration, Instant},
+};
 
 use rustfft::{
     num_complex::{Complex, ComplexFloat},
@@ -11,32 +15,468 @@ use rustfft::{
 
 use crate::core::note::{HasPrimaryHarmonicSeries, ALL_PITCH_NOTES_WITH_FREQUENCY};
 
-use crate::core::{base::Res, note::Note, pitch::HasFrequency};
+use crate::core::{
+    base::{KordError, Res},
+    chord::{Chord, HasInversion, HasRoot, HasSlash, Inversion},
+    note::Note,
+    pitch::{tuning, HasFrequency, HasPitch},
+};
+
+use super::chroma::{get_chord_from_chroma, get_chroma};
 // use crate::helpers::plot_frequency_space;
 
+// Statics.
+
+/// A spectral flatness threshold that separates tonal frames from noise-like ones (see [`AnalysisConfig::flatness_threshold`]).
+pub static DEFAULT_FLATNESS_THRESHOLD: f32 = 0.3;
+
+/// The number of periods of a frequency that a window must span for the frequency to be reliably resolved.
+pub static MIN_PERIODS_PER_WINDOW: f32 = 4.0;
+
+/// The lowest frequency that contributes to the spectral flatness.
+pub static FLATNESS_MIN_FREQUENCY: f32 = 50.0;
+
+/// The highest frequency that contributes to the spectral flatness.
+pub static FLATNESS_MAX_FREQUENCY: f32 = 8_000.0;
+
+// Enum.
+
+/// The method used to detect chords from audio.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Backend {
+    /// Detects the notes (via peak picking and harmonic reduction), and then guesses the chord from them.
+    #[default]
+    Peaks,
+    /// Correlates the chroma vector of the audio against binary templates of each chord type (see [`super::chroma`]).
+    ChromaTemplate,
+}
+
+/// A coherent bundle of analysis options for a common goal, trading precision for speed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum AnalysisPreset {
+    /// Favors latency (e.g., real-time listening): short, non-overlapping windows (with a Hamming window, which keeps the
+    /// leakage of the short windows down), no zero-padding, and the cheaper chroma backend.
+    Fast,
+    /// The defaults: the peaks backend, with half-overlapping (rectangular) windows.
+    #[default]
+    Balanced,
+    /// Favors precision (e.g., offline analysis of a file): long, heavily overlapping windows (with a Hann window), a
+    /// zero-padded (larger) FFT, and the noise gate.
+    Accurate,
+}
+
+impl AnalysisPreset {
+    /// Returns the [`AnalysisConfig`] of the preset.
+    pub fn config(&self) -> AnalysisConfig {
+        match self {
+            AnalysisPreset::Fast => AnalysisConfig::default().with_backend(Backend::ChromaTemplate).with_window(WindowFunction::Hamming),
+            AnalysisPreset::Balanced => AnalysisConfig::default(),
+            AnalysisPreset::Accurate => AnalysisConfig::default()
+                .with_window(WindowFunction::Hann)
+                .with_zero_pad_factor(4)
+                .with_flatness_threshold(DEFAULT_FLATNESS_THRESHOLD),
+        }
+    }
+
+    /// Returns the window length (in seconds) of the preset, for windowed analysis.
+    pub fn window_in_seconds(&self) -> f32 {
+        match self {
+            AnalysisPreset::Fast => 0.25,
+            AnalysisPreset::Balanced => 0.5,
+            AnalysisPreset::Accurate => 1.0,
+        }
+    }
+
+    /// Returns the hop length (in seconds) of the preset, for windowed analysis (i.e., the windows overlap by the difference
+    /// between this and [`AnalysisPreset::window_in_seconds`]).
+    pub fn hop_in_seconds(&self) -> f32 {
+        match self {
+            AnalysisPreset::Fast => 0.25,
+            AnalysisPreset::Balanced => 0.25,
+            AnalysisPreset::Accurate => 0.125,
+        }
+    }
+}
+
+/// The window function that the audio data is multiplied by before the FFT.
+///
+/// Tapering the edges of the data reduces spectral leakage (i.e., a strong peak "smearing" into the bins around it), at the
+/// cost of slightly wider peaks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum WindowFunction {
+    /// No window (i.e., the data is used as-is).
+    #[default]
+    Rectangular,
+    /// The [Hann](https://en.wikipedia.org/wiki/Hann_function) window.
+    Hann,
+    /// The Hamming window, which does not taper all of the way to zero.
+    Hamming,
+    /// The Blackman window, which leaks the least, but has the widest peaks.
+    Blackman,
+}
+
+impl WindowFunction {
+    /// Returns the data, multiplied by the window function.
+    pub fn apply(&self, data: &[f32]) -> Vec<f32> {
+        if *self == WindowFunction::Rectangular || data.len() < 2 {
+            return data.to_vec();
+        }
+
+        let last = (data.len() - 1) as f32;
+
+        data.iter()
+            .enumerate()
+            .map(|(k, sample)| {
+                let x = 2.0 * std::f32::consts::PI * k as f32 / last;
+
+                let weight = match self {
+                    WindowFunction::Rectangular => 1.0,
+                    WindowFunction::Hann => 0.5 - 0.5 * x.cos(),
+                    WindowFunction::Hamming => 0.54 - 0.46 * x.cos(),
+                    WindowFunction::Blackman => 0.42 - 0.5 * x.cos() + 0.08 * (2.0 * x).cos(),
+                };
+
+                sample * weight
+            })
+            .collect()
+    }
+}
+
+// Struct.
+
+/// Options for the audio analysis.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnalysisConfig {
+    /// The factor by which the FFT input is zero-padded (e.g., `4` runs the FFT over four times as many samples as the input).
+    ///
+    /// Zero-padding interpolates the spectrum, which yields finer frequency estimates for short buffers at the cost of a larger FFT.
+    pub zero_pad_factor: usize,
+    /// The fraction of the strongest peak's magnitude that other peaks must exceed to be considered notes.
+    pub peak_cutoff: f32,
+    /// The (absolute) magnitude that a peak must exceed to be considered at all (raising it drops weak, spurious partials,
+    /// e.g., in noisy recordings).
+    pub magnitude_threshold: f32,
+    /// The lowest frequency (in Hz) of the peaks that are considered notes.
+    pub min_frequency: f32,
+    /// The highest frequency (in Hz) of the peaks that are considered notes.
+    pub max_frequency: f32,
+    /// The window function that the audio data is multiplied by before the FFT.
+    pub window: WindowFunction,
+    /// The method used to detect chords.
+    pub backend: Backend,
+    /// The strength of the bias toward chords that are related to the previously detected chord, when detecting a sequence of
+    /// chords (`0` disables it).
+    ///
+    /// The bias is added to the score of each related candidate.  For the [`Backend::ChromaTemplate`] backend, related
+    /// candidates are diatonically related to the previous chord, and the scores are in `[0, 1]`, so small values (e.g.,
+    /// `0.05`) only tip borderline detections.  For the [`Backend::Peaks`] backend, the candidates are the readings of the
+    /// detected notes (see [`Chord::try_from_notes_ranked`]), related candidates share the root of the previous chord, and the
+    /// scores count chord tones, so larger values (e.g., `0.5`) are needed to tip ambiguous readings.
+    pub temporal_prior: f32,
+    /// The spectral flatness above which a frame is considered noise (e.g., a drum hit), and no notes are detected in it
+    /// (`None` disables the check).
+    ///
+    /// The flatness is `0` for a pure tone, and approaches `1` for white noise (see [`spectral_flatness`]); chords stay well below
+    /// [`DEFAULT_FLATNESS_THRESHOLD`].
+    pub flatness_threshold: Option<f32>,
+}
+
+impl Default for AnalysisConfig {
+    fn default() -> Self {
+        Self {
+            zero_pad_factor: 1,
+            peak_cutoff: 0.1,
+            magnitude_threshold: 0.1,
+            min_frequency: 0.0,
+            max_frequency: f32::INFINITY,
+            window: WindowFunction::default(),
+            backend: Backend::default(),
+            temporal_prior: 0.0,
+            flatness_threshold: None,
+        }
+    }
+}
+
+impl AnalysisConfig {
+    /// Sets the zero-pad factor of the config, and returns a new config.
+    pub fn with_zero_pad_factor(self, zero_pad_factor: usize) -> Self {
+        Self { zero_pad_factor, ..self }
+    }
+
+    /// Sets the peak cutoff of the config, and returns a new config.
+    pub fn with_peak_cutoff(self, peak_cutoff: f32) -> Self {
+        Self { peak_cutoff, ..self }
+    }
+
+    /// Sets the magnitude threshold of the config, and returns a new config.
+    pub fn with_magnitude_threshold(self, magnitude_threshold: f32) -> Self {
+        Self { magnitude_threshold, ..self }
+    }
+
+    /// Sets the range of frequencies (in Hz) of the peaks that are considered notes, and returns a new config.
+    pub fn with_frequency_range(self, min_frequency: f32, max_frequency: f32) -> Self {
+        Self { min_frequency, max_frequency, ..self }
+    }
+
+    /// Sets the window function of the config, and returns a new config.
+    pub fn with_window(self, window: WindowFunction) -> Self {
+        Self { window, ..self }
+    }
+
+    /// Sets the chord detection backend of the config, and returns a new config.
+    pub fn with_backend(self, backend: Backend) -> Self {
+        Self { backend, ..self }
+    }
+
+    /// Sets the temporal prior strength of the config, and returns a new config.
+    pub fn with_temporal_prior(self, temporal_prior: f32) -> Self {
+        Self { temporal_prior, ..self }
+    }
+
+    /// Sets the spectral flatness threshold of the config (enabling the noise check), and returns a new config.
+    pub fn with_flatness_threshold(self, flatness_threshold: f32) -> Self {
+        Self {
+            flatness_threshold: Some(flatness_threshold),
+            ..self
+        }
+    }
+
+    /// Returns the number of samples in the FFT of an input of `num_samples` samples (i.e., including the zero-padding).
+    pub fn fft_size(&self, num_samples: usize) -> usize {
+        num_samples * self.zero_pad_factor.max(1)
+    }
+}
+
+impl From<AnalysisPreset> for AnalysisConfig {
+    fn from(preset: AnalysisPreset) -> Self {
+        preset.config()
+    }
+}
+
+// Functions.
+
 /// Gets notes from audio data.
 pub fn get_notes_from_audio_data(data: &[f32], length_in_seconds: f32) -> Res<Vec<Note>> {
-    if length_in_seconds < 0.2 {
-        return Err(anyhow::Error::msg("Listening length in seconds must be greater than 0.2."));
+    get_notes_from_audio_data_with_config(data, length_in_seconds, &AnalysisConfig::default())
+}
+
+/// Gets notes from the last `length_in_seconds` of raw samples (e.g., from the Web Audio API) at the given sample rate.
+///
+/// This is a pure function of the samples (i.e., it needs no audio device, and assumes no particular sample rate), so it is
+/// the entry point for analysis on targets without `cpal` (e.g., `wasm32-unknown-unknown`).  The frequencies are computed from
+/// the true sample rate (rather than from a length in seconds that may be slightly off the number of samples).  Errors if the
+/// sample rate is zero, or if there are fewer than `length_in_seconds` of samples.
+pub fn analyze_samples(data: &[f32], sample_rate: u32, length_in_seconds: f32) -> Res<Vec<Note>> {
+    if sample_rate == 0 {
+        return Err(KordError::InvalidArgument("Sample rate must be greater than 0.".to_string()).into());
     }
 
-    let num_nan = data.iter().filter(|n| n.is_nan()).count();
-    if num_nan > 0 {
-        return Err(anyhow::Error::msg(format!("{num_nan} NaNs in audio data.")));
+    let num_samples = (sample_rate as f32 * length_in_seconds).round() as usize;
+
+    if num_samples > data.len() {
+        return Err(KordError::InvalidArgument(format!("Expected at least {num_samples} samples ({length_in_seconds}s at {sample_rate} Hz), but got {}.", data.len())).into());
     }
 
-    let frequency_space = get_frequency_space(data, length_in_seconds);
+    get_notes_from_audio_data(&data[data.len() - num_samples..], num_samples as f32 / sample_rate as f32)
+}
 
-    // Smooth the frequency space.
+/// Gets notes from audio data, using the given [`AnalysisConfig`].
+pub fn get_notes_from_audio_data_with_config(data: &[f32], length_in_seconds: f32, config: &AnalysisConfig) -> Res<Vec<Note>> {
+    Ok(get_notes_with_magnitudes_from_audio_data(data, length_in_seconds, config)?.into_iter().map(|(n, _)| n).collect())
+}
 
-    let smoothed_frequency_space = get_smoothed_frequency_space(&frequency_space, length_in_seconds);
-    //plot_frequency_space(&smoothed_frequency_space, "frequency_space", 100f32, 1000f32);
+/// Gets notes from audio data, along with the confidence of each detection, sorted by descending confidence.
+///
+/// The confidence is the spectral magnitude of the note relative to the strongest note (i.e., in `(0, 1]`), so it is
+/// comparable across clips of different lengths, and can be used with a fixed cutoff (e.g., to drop weak partials).
+pub fn get_notes_with_confidence_from_audio_data(data: &[f32], length_in_seconds: f32) -> Res<Vec<(Note, f32)>> {
+    get_notes_with_confidence_from_audio_data_with_config(data, length_in_seconds, &AnalysisConfig::default())
+}
+
+/// Gets notes from audio data, along with the confidence of each detection, sorted by descending confidence, using the given
+/// [`AnalysisConfig`].
+pub fn get_notes_with_confidence_from_audio_data_with_config(data: &[f32], length_in_seconds: f32, config: &AnalysisConfig) -> Res<Vec<(Note, f32)>> {
+    let mut result = get_notes_with_magnitudes_from_audio_data(data, length_in_seconds, config)?;
+
+    result.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
 
-    Ok(get_notes_from_smoothed_frequency_space(&smoothed_frequency_space, length_in_seconds))
+    Ok(result)
+}
+
+/// Gets notes from audio data, along with their magnitudes relative to the strongest note (i.e., in `(0, 1]`).
+pub(crate) fn get_notes_with_magnitudes_from_audio_data(data: &[f32], length_in_seconds: f32, config: &AnalysisConfig) -> Res<Vec<(Note, f32)>> {
+    let Some(smoothed_frequency_space) = get_smoothed_frequency_space_from_audio_data(data, length_in_seconds, config)? else {
+        return Ok(Vec::new());
+    };
+    let padded_length_in_seconds = length_in_seconds * config.zero_pad_factor.max(1) as f32;
+
+    Ok(get_notes_with_magnitudes_from_smoothed_frequency_space(&smoothed_frequency_space, padded_length_in_seconds, config))
+}
+
+/// Gets notes from audio data, along with the detected frequency that was mapped to each note.
+///
+/// Useful for debugging mis-mappings: the frequency is that of the strongest spectral peak that maps to the note, refined
+/// (by parabolic interpolation) to a fraction of a bin.
+pub fn get_notes_with_frequencies_from_audio_data(data: &[f32], length_in_seconds: f32) -> Res<Vec<(f32, Note)>> {
+    get_notes_with_frequencies_from_audio_data_with_config(data, length_in_seconds, &AnalysisConfig::default())
+}
+
+/// Gets notes from audio data, along with the detected frequency that was mapped to each note, using the given [`AnalysisConfig`].
+pub fn get_notes_with_frequencies_from_audio_data_with_config(data: &[f32], length_in_seconds: f32, config: &AnalysisConfig) -> Res<Vec<(f32, Note)>> {
+    let Some(smoothed_frequency_space) = get_smoothed_frequency_space_from_audio_data(data, length_in_seconds, config)? else {
+        return Ok(Vec::new());
+    };
+    let padded_length_in_seconds = length_in_seconds * config.zero_pad_factor.max(1) as f32;
+
+    let notes = get_notes_with_magnitudes_from_smoothed_frequency_space(&smoothed_frequency_space, padded_length_in_seconds, config);
+    let peak_space = translate_frequency_space_to_peak_space(&smoothed_frequency_space, padded_length_in_seconds);
+    let ratio = tuning().ratio();
+
+    let result = notes
+        .into_iter()
+        .map(|(note, _)| {
+            let peak = peak_space
+                .iter()
+                .filter(|(f, m)| *m > 0.0 && binary_search_closest(ALL_PITCH_NOTES_WITH_FREQUENCY.deref(), *f / ratio, |t| t.1).map(|t| t.0) == Some(note))
+                .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+
+            let frequency = match peak {
+                Some((frequency, _)) => interpolate_peak_frequency(&smoothed_frequency_space, *frequency),
+                None => note.frequency(),
+            };
+
+            (frequency, note)
+        })
+        .collect();
+
+    Ok(result)
+}
+
+/// Gets the most likely chord from audio data, using the [`Backend`] of the given [`AnalysisConfig`].
+///
+/// Returns `None` if no chord could be detected.
+pub fn get_chord_from_audio_data_with_config(data: &[f32], length_in_seconds: f32, config: &AnalysisConfig) -> Res<Option<Chord>> {
+    match config.backend {
+        Backend::Peaks => {
+            let notes = get_notes_from_audio_data_with_config(data, length_in_seconds, config)?;
+
+            Ok(Chord::try_from_notes(&notes).ok().and_then(|c| c.into_iter().next()))
+        }
+        Backend::ChromaTemplate => {
+            if length_in_seconds < 0.2 {
+                return Err(KordError::ListeningLengthTooShort.into());
+            }
+
+            let data = sanitize_audio_data(data);
+
+            if is_noise_like(&get_frequency_space(&data, length_in_seconds), config) {
+                return Ok(None);
+            }
+
+            Ok(get_chord_from_chroma(&get_chroma(&data, length_in_seconds)))
+        }
+    }
+}
+
+/// Gets all of the equally likely names of the chord in the audio data (e.g., both `C6` and `Am7`), rather than arbitrarily
+/// choosing one (see [`Chord::co_equal_interpretations`]).
+///
+/// Returns an empty vector if no chord could be detected.
+pub fn get_co_equal_chords_from_audio_data(data: &[f32], length_in_seconds: f32) -> Res<Vec<Chord>> {
+    let notes = get_notes_from_audio_data(data, length_in_seconds)?;
+
+    Ok(Chord::co_equal_interpretations(&notes).unwrap_or_default())
+}
+
+/// Detects the chord in the audio data (sampled at `sample_rate`), along with the inversion that is sounding.
+///
+/// The chord is named from the pitch classes of the detected notes (see [`Chord::from_pitch_class_set`]), and the inversion
+/// is determined by which chord tone the lowest detected note (the bass) is.  Returns `None` if no chord could be detected.
+pub fn detect_chord_with_inversion(data: &[f32], sample_rate: u32) -> Option<(Chord, Inversion)> {
+    let notes = get_notes_from_audio_data(data, data.len() as f32 / sample_rate as f32).ok()?;
+    let bass = notes.iter().min().copied()?;
+
+    let pitch_classes = notes.iter().map(|n| n.pitch() as u8).collect::<Vec<_>>();
+    let chord = Chord::from_pitch_class_set(&pitch_classes).ok()?.into_iter().find(|c| c.inversion() == 0 && c.slash() == c.root())?;
+
+    let chord = chord.inversion_with_bass(bass).ok()?;
+    let inversion = Inversion::try_from(chord.inversion()).ok()?;
+
+    Some((chord, inversion))
+}
+
+/// Measures how long a single chord detection pass over the audio data (sampled at `sample_rate`) takes with the given
+/// [`AnalysisConfig`] on the current hardware.
+///
+/// Useful for tuning the window size (and the config) to a latency budget at runtime.  The detection result is discarded.
+pub fn measure_detection_latency(data: &[f32], sample_rate: u32, config: &AnalysisConfig) -> Duration {
+    let length_in_seconds = data.len() as f32 / sample_rate as f32;
+
+    let start = Instant::now();
+    let _ = get_chord_from_audio_data_with_config(data, length_in_seconds, config);
+
+    start.elapsed()
+}
+
+/// Returns the minimum FFT size (in samples, rounded up to a power of two) needed to reliably resolve the given frequency
+/// at the given sample rate (i.e., to span [`MIN_PERIODS_PER_WINDOW`] of its periods).
+///
+/// Lower notes (e.g., from a bass) need longer windows.  Returns `0` if either the frequency or the sample rate is not positive.
+pub fn min_window_for_frequency(freq: f32, sample_rate: f32) -> usize {
+    if freq <= 0.0 || sample_rate <= 0.0 {
+        return 0;
+    }
+
+    ((MIN_PERIODS_PER_WINDOW * sample_rate / freq).ceil() as usize).next_power_of_two()
+}
+
+/// Gets notes from a pre-computed spectrum of (frequency, magnitude) pairs, skipping the FFT entirely.
+///
+/// The spectrum does not need to be uniformly spaced: it is resampled onto 1 Hz bins (keeping the largest magnitude in each bin),
+/// and normalized, before running the usual peak detection and note mapping.  Frequencies above 8 kHz are ignored.
+pub fn notes_from_spectrum(spectrum: &[(f32, f32)], config: &AnalysisConfig) -> Vec<Note> {
+    const NUM_BINS: usize = 8_192;
+
+    let mut resampled_spectrum = (0..NUM_BINS).map(|k| (k as f32, 0.0f32)).collect::<Vec<_>>();
+
+    for (frequency, magnitude) in spectrum {
+        let k = frequency.round();
+
+        if !magnitude.is_finite() || k < 0.0 || k >= NUM_BINS as f32 {
+            continue;
+        }
+
+        let bin = &mut resampled_spectrum[k as usize].1;
+        *bin = bin.max(*magnitude);
+    }
+
+    let max_magnitude = resampled_spectrum.iter().map(|(_, m)| *m).fold(0.0, f32::max);
+
+    if max_magnitude <= 0.0 {
+        return Vec::new();
+    }
+
+    for (_, magnitude) in &mut resampled_spectrum {
+        *magnitude /= max_magnitude;
+    }
+
+    // The resampled spectrum has a 1 Hz resolution, which is what a one second buffer yields.
+    get_notes_with_magnitudes_from_smoothed_frequency_space(&resampled_spectrum, 1.0, config)
+        .into_iter()
+        .map(|(n, _)| n)
+        .collect()
 }
 
 /// Gets notes from pre-smoothed frequency data (helps with model training deterministic features).
 pub fn get_notes_from_smoothed_frequency_space(smoothed_frequency_space: &[(f32, f32)], length_in_seconds: f32) -> Vec<Note> {
+    get_notes_with_magnitudes_from_smoothed_frequency_space(smoothed_frequency_space, length_in_seconds, &AnalysisConfig::default())
+        .into_iter()
+        .map(|(n, _)| n)
+        .collect()
+}
+
+/// Gets notes (and their relative magnitudes) from pre-smoothed frequency data, using the given [`AnalysisConfig`].
+fn get_notes_with_magnitudes_from_smoothed_frequency_space(smoothed_frequency_space: &[(f32, f32)], length_in_seconds: f32, config: &AnalysisConfig) -> Vec<(Note, f32)> {
     // Translate the frequency space into a "peak space" (dampen values that are not the "peak" of a specified window).
 
     let peak_space = translate_frequency_space_to_peak_space(smoothed_frequency_space, length_in_seconds);
@@ -44,7 +484,7 @@ pub fn get_notes_from_smoothed_frequency_space(smoothed_frequency_space: &[(f32,
 
     // Bucket top N bins into their proper notes, and keep "magnitude".
 
-    let peak_best_notes = get_likely_notes_from_peak_space(&peak_space, 0.1);
+    let peak_best_notes = get_likely_notes_from_peak_space(&peak_space, config);
     //.into_iter().map(|(n, _)| n).collect::<Vec<_>>();
     let best_notes = peak_best_notes;
     //let binned_best_notes = get_likely_notes_using_bins(smoothed_frequency_space, 0.5, 0.1);
@@ -56,9 +496,58 @@ pub fn get_notes_from_smoothed_frequency_space(smoothed_frequency_space: &[(f32,
     reduce_notes_by_harmonic_series(&best_notes, 0.1)
 }
 
+/// Computes the spectral flatness of the frequency space (i.e., the ratio of the geometric mean to the arithmetic mean of
+/// the power spectrum), between [`FLATNESS_MIN_FREQUENCY`] and [`FLATNESS_MAX_FREQUENCY`].
+///
+/// The flatness is close to `0` for tonal audio (where the power is concentrated in a few peaks), and close to `1` for noise
+/// (where the power is spread evenly).  Silence has a flatness of `0`.
+pub fn spectral_flatness(frequency_space: &[(f32, f32)]) -> f32 {
+    let powers = frequency_space
+        .iter()
+        .filter(|(f, _)| (FLATNESS_MIN_FREQUENCY..=FLATNESS_MAX_FREQUENCY).contains(f))
+        .map(|(_, m)| (*m as f64).powi(2))
+        .collect::<Vec<_>>();
+
+    let arithmetic_mean = powers.iter().sum::<f64>() / powers.len().max(1) as f64;
+
+    if arithmetic_mean <= 0.0 {
+        return 0.0;
+    }
+
+    let geometric_mean = (powers.iter().map(|p| (p + 1e-20).ln()).sum::<f64>() / powers.len() as f64).exp();
+
+    (geometric_mean / arithmetic_mean) as f32
+}
+
+/// Returns whether or not the frequency space is too noise-like to analyze, per the [`AnalysisConfig::flatness_threshold`].
+pub(crate) fn is_noise_like(frequency_space: &[(f32, f32)], config: &AnalysisConfig) -> bool {
+    config.flatness_threshold.map(|threshold| spectral_flatness(frequency_space) > threshold).unwrap_or(false)
+}
+
+/// Sanitizes audio data by replacing non-finite (NaN / infinite) and denormal samples with zero.
+///
+/// This guards the FFT against bad input from upstream sources.
+pub fn sanitize_audio_data(data: &[f32]) -> Vec<f32> {
+    data.iter().map(|n| if n.is_finite() && !n.is_subnormal() { *n } else { 0.0 }).collect()
+}
+
+/// Returns whether or not the audio data is silent (i.e., empty, or all zeros).
+pub(crate) fn is_silent(data: &[f32]) -> bool {
+    data.iter().all(|s| *s == 0.0)
+}
+
 /// Gets the frequency space from the audio data.
 pub fn get_frequency_space(data: &[f32], length_in_seconds: f32) -> Vec<(f32, f32)> {
-    let num_samples = data.len();
+    get_zero_padded_frequency_space(data, length_in_seconds, 1)
+}
+
+/// Gets the frequency space from the audio data, after zero-padding the data to `zero_pad_factor` times its length.
+///
+/// The resulting bins are `zero_pad_factor` times denser than those of [`get_frequency_space`].
+pub fn get_zero_padded_frequency_space(data: &[f32], length_in_seconds: f32, zero_pad_factor: usize) -> Vec<(f32, f32)> {
+    let zero_pad_factor = zero_pad_factor.max(1);
+    let num_samples = data.len() * zero_pad_factor;
+    let padded_length_in_seconds = length_in_seconds * zero_pad_factor as f32;
 
     // Perform the FFT.
 
@@ -66,9 +555,10 @@ pub fn get_frequency_space(data: &[f32], length_in_seconds: f32) -> Vec<(f32, f3
     let fft = planner.plan_fft_forward(num_samples);
 
     let mut buffer = data.iter().map(|n| Complex::new(*n, 0.0)).collect::<Vec<_>>();
+    buffer.resize(num_samples, Complex::new(0.0, 0.0));
     fft.process(&mut buffer);
 
-    buffer.into_iter().enumerate().map(|(k, d)| (k as f32 / length_in_seconds, d.abs())).collect::<Vec<_>>()
+    buffer.into_iter().enumerate().map(|(k, d)| (k as f32 / padded_length_in_seconds, d.abs())).collect::<Vec<_>>()
 }
 
 /// Gets the time space from the frequency space.
@@ -233,31 +723,108 @@ pub fn translate_frequency_space_to_peak_space(frequency_space: &[(f32, f32)], l
     peak_space.into_iter().skip(min_index).take(max_index - min_index).collect()
 }
 
-/// Get likely notes from the peak space.
-fn get_likely_notes_from_peak_space(peak_space: &[(f32, f32)], cutoff: f32) -> Vec<(Note, f32)> {
-    let mut peak_space = peak_space.iter().filter(|(_, m)| *m > 0.1).copied().collect::<Vec<_>>();
+/// Gets the smoothed frequency space of the audio data (which is zero-padded by [`AnalysisConfig::zero_pad_factor`]).
+///
+/// Returns `None` if the data is silent (or empty), or if the frame is noise-like (see [`AnalysisConfig::flatness_threshold`]).
+fn get_smoothed_frequency_space_from_audio_data(data: &[f32], length_in_seconds: f32, config: &AnalysisConfig) -> Res<Option<Vec<(f32, f32)>>> {
+    if length_in_seconds < 0.2 {
+        return Err(KordError::ListeningLengthTooShort.into());
+    }
+
+    let data = sanitize_audio_data(data);
+
+    // Empty and silent buffers (e.g., silent frames of a stream) have no notes.
+
+    if is_silent(&data) {
+        return Ok(None);
+    }
+
+    let data = config.window.apply(&data);
+
+    // Zero-padding makes the spectrum look like that of a proportionally longer buffer.
+
+    let zero_pad_factor = config.zero_pad_factor.max(1);
+    let padded_length_in_seconds = length_in_seconds * zero_pad_factor as f32;
+
+    let frequency_space = get_zero_padded_frequency_space(&data, length_in_seconds, zero_pad_factor);
+
+    // Skip noise-like frames (e.g., drum hits), whose broadband spectra would otherwise produce spurious notes.
+
+    if is_noise_like(&frequency_space, config) {
+        return Ok(None);
+    }
+
+    // Smooth the frequency space.
+
+    let smoothed_frequency_space = get_smoothed_frequency_space(&frequency_space, padded_length_in_seconds);
+    //plot_frequency_space(&smoothed_frequency_space, "frequency_space", 100f32, 1000f32);
+
+    Ok(Some(smoothed_frequency_space))
+}
+
+/// Refines the frequency of a peak in the frequency space by fitting a parabola through the peak bin and its neighbors.
+fn interpolate_peak_frequency(frequency_space: &[(f32, f32)], frequency: f32) -> f32 {
+    let Some(k) = frequency_space.iter().position(|(f, _)| *f == frequency) else {
+        return frequency;
+    };
+
+    if k == 0 || k + 1 >= frequency_space.len() {
+        return frequency;
+    }
+
+    let (previous, peak, next) = (frequency_space[k - 1].1, frequency_space[k].1, frequency_space[k + 1].1);
+    let denominator = previous - 2.0 * peak + next;
+
+    if denominator == 0.0 {
+        return frequency;
+    }
+
+    let offset = (0.5 * (previous - next) / denominator).clamp(-0.5, 0.5);
+    let bin_width = frequency_space[k + 1].0 - frequency_space[k].0;
+
+    frequency + offset * bin_width
+}
+
+/// Get likely notes from the peak space, using the thresholds and frequency range of the given [`AnalysisConfig`].
+fn get_likely_notes_from_peak_space(peak_space: &[(f32, f32)], config: &AnalysisConfig) -> Vec<(Note, f32)> {
+    let frequency_range = config.min_frequency..=config.max_frequency;
+
+    let mut peak_space = peak_space
+        .iter()
+        .filter(|(f, m)| *m > config.magnitude_threshold && frequency_range.contains(f))
+        .copied()
+        .collect::<Vec<_>>();
     peak_space.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
 
-    let max_power = peak_space[0].1;
+    let Some(max_power) = peak_space.first().map(|(_, m)| *m) else {
+        return Vec::new();
+    };
 
     // Take all peaks with 10% or more of the max power.
-    let peak_space = peak_space.into_iter().filter(|(_, m)| *m > max_power * cutoff).collect::<Vec<_>>();
+    let peak_space = peak_space.into_iter().filter(|(_, m)| *m > max_power * config.peak_cutoff).collect::<Vec<_>>();
 
+    let ratio = tuning().ratio();
     let mut candidates = HashMap::new();
 
     for (frequency, magnitude) in &peak_space {
-        if let Some(pair) = binary_search_closest(ALL_PITCH_NOTES_WITH_FREQUENCY.deref(), *frequency, |t| t.1) {
+        // The table is in the standard tuning, so compare in the standard tuning.
+        if let Some(pair) = binary_search_closest(ALL_PITCH_NOTES_WITH_FREQUENCY.deref(), *frequency / ratio, |t| t.1) {
             let note = pair.0;
             let entry = candidates.entry(note).or_insert(*magnitude);
             *entry += magnitude;
         }
     }
 
-    candidates.into_iter().collect::<Vec<_>>()
+    let mut candidates = candidates.into_iter().collect::<Vec<_>>();
+    candidates.sort_by(|a, b| a.0.cmp(&b.0));
+
+    candidates
 }
 
 /// Reduce a vector of notes by removing all notes that are part of the harmonic series of another note.
-fn reduce_notes_by_harmonic_series(notes: &[(Note, f32)], cutoff: f32) -> Vec<Note> {
+///
+/// The remaining notes are returned in ascending order, along with their magnitudes relative to the strongest note.
+fn reduce_notes_by_harmonic_series(notes: &[(Note, f32)], cutoff: f32) -> Vec<(Note, f32)> {
     let mut working_set = notes.to_vec();
     working_set.sort_unstable_by(|a, b| a.0.frequency().partial_cmp(&b.0.frequency()).unwrap());
 
@@ -291,10 +858,17 @@ fn reduce_notes_by_harmonic_series(notes: &[(Note, f32)], cutoff: f32) -> Vec<No
 
     // Remove notes that are below the threshold.
 
-    let cutoff = working_set[0].1 * cutoff;
+    let Some(max_magnitude) = working_set.first().map(|(_, m)| *m) else {
+        return Vec::new();
+    };
+    let cutoff = max_magnitude * cutoff;
     working_set.retain(|(_, magnitude)| *magnitude > cutoff);
 
-    working_set.into_iter().map(|(note, _)| note).collect()
+    // Report the notes in a deterministic order (ascending by frequency).
+
+    working_set.sort_by(|a, b| a.0.cmp(&b.0));
+
+    working_set.into_iter().map(|(note, magnitude)| (note, magnitude / max_magnitude)).collect()
 }
 
 /// For every note, get its "frequency window", which is halfway between the frequency of the note and the frequency of
@@ -370,11 +944,18 @@ where
 
 #[cfg(test)]
 pub(crate) mod tests {
-    use std::{fs::File, io::Read};
-
-    use crate::core::note::ALL_PITCH_NOTES;
+    use std::{f32::consts::PI, fs::File, io::Read};
+
+    use crate::core::{
+        chord::{HasChord, HasKnownChord},
+        helpers::generate_tone,
+        known_chord::KnownChord,
+        note::{AFlat, CThree, EThree, GThree, A, ALL_PITCH_NOTES, C, E, G},
+        pitch::{with_tuning, TuningContext},
+    };
 
     use super::*;
+    use pretty_assertions::assert_eq;
 
     pub fn load_test_data() -> Vec<f32> {
         let mut file = File::open("tests/vec.bin").unwrap();
@@ -399,9 +980,162 @@ pub(crate) mod tests {
     }
 
     #[test]
-    #[should_panic]
+    fn test_errors() {
+        let error = get_notes_from_audio_data(&[0.0; 100], 0.1).unwrap_err();
+
+        assert_eq!(error.downcast_ref::<KordError>(), Some(&KordError::ListeningLengthTooShort));
+        assert_eq!(error.to_string(), "Listening length in seconds must be greater than 0.2.");
+
+        let error = KordError::NoInputDevice {
+            requested: Some("Studio".to_string()),
+            available: vec!["Built-in".to_string(), "USB".to_string()],
+        };
+        assert_eq!(error.to_string(), "Could not find an input device named `Studio` (available devices: Built-in, USB).");
+
+        assert_eq!(KordError::Timeout(Duration::from_secs(2)).to_string(), "The input device did not deliver any audio within 2.0 seconds.");
+    }
+
+    #[test]
+    fn test_analyze_samples() {
+        let tone = |sample_rate: u32, length_in_seconds: f32| {
+            (0..(sample_rate as f32 * length_in_seconds) as usize)
+                .map(|k| {
+                    let t = k as f32 / sample_rate as f32;
+
+                    [C.frequency(), E.frequency(), G.frequency()].iter().map(|f| (2.0 * PI * f * t).sin() * 0.25).sum()
+                })
+                .collect::<Vec<f32>>()
+        };
+
+        for sample_rate in [16_000, 22_050, 44_100, 48_000, 96_000] {
+            assert_eq!(analyze_samples(&tone(sample_rate, 1.0), sample_rate, 1.0).unwrap(), vec![C, E, G], "{sample_rate} Hz");
+        }
+
+        // Only the last part of the buffer is analyzed.
+        let mut data = vec![0.0; 48_000];
+        data.extend(tone(48_000, 0.5));
+        assert_eq!(analyze_samples(&data, 48_000, 0.5).unwrap(), vec![C, E, G]);
+        assert_eq!(analyze_samples(&data[..48_000], 48_000, 0.5).unwrap(), vec![]);
+
+        assert!(analyze_samples(&data, 0, 0.5).is_err());
+        assert!(analyze_samples(&data, 48_000, 2.0).is_err());
+    }
+
+    #[test]
+    fn test_sample_rate() {
+        // An A sampled at 48 kHz.
+        let data = generate_tone(&[A.frequency()], 1.0, 48_000);
+
+        assert_eq!(analyze_samples(&data, 48_000, 1.0).unwrap(), vec![A]);
+        assert_eq!(get_notes_from_audio_data(&data, 1.0).unwrap(), vec![A]);
+
+        // A length that is off shifts the frequencies (here, by about a semitone and a half).
+        assert_ne!(get_notes_from_audio_data(&data, 1.09).unwrap(), vec![A]);
+
+        // So does assuming the wrong sample rate.
+        assert_ne!(analyze_samples(&data, 44_100, 1.0).unwrap(), vec![A]);
+        assert_eq!(analyze_samples(&generate_tone(&[A.frequency()], 1.0, 44_100), 44_100, 1.0).unwrap(), vec![A]);
+    }
+
+    #[test]
+    fn test_sanitize_audio_data() {
+        assert_eq!(
+            sanitize_audio_data(&[0.5, f32::NAN, f32::INFINITY, f32::NEG_INFINITY, f32::MIN_POSITIVE / 2.0, -0.5]),
+            vec![0.5, 0.0, 0.0, 0.0, 0.0, -0.5]
+        );
+    }
+
+    #[test]
     fn test_get_notes_from_audio_data_nan() {
-        get_notes_from_audio_data(&[0.0, 0.0, f32::NAN], 10.0).unwrap();
+        let mut data = generate_tone(&[A.frequency()], 1.0, 44_100);
+        data[100] = f32::NAN;
+        data[200] = f32::INFINITY;
+        data[300] = f32::NEG_INFINITY;
+
+        let notes = get_notes_from_audio_data(&data, 1.0).unwrap();
+
+        assert_eq!(notes, vec![A]);
+    }
+
+    #[test]
+    fn test_zero_padding() {
+        // A tone that falls between the (5 Hz) bins of a 0.2s buffer.
+        let frequency = 442.5;
+        let data = generate_tone(&[frequency], 0.2, 44_100);
+
+        let peak_frequency = |frequency_space: Vec<(f32, f32)>| {
+            frequency_space
+                .into_iter()
+                .filter(|(f, _)| (400.0..500.0).contains(f))
+                .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+                .unwrap()
+                .0
+        };
+
+        let unpadded_error = (peak_frequency(get_frequency_space(&data, 0.2)) - frequency).abs();
+        let padded_error = (peak_frequency(get_zero_padded_frequency_space(&data, 0.2, 4)) - frequency).abs();
+
+        assert!(padded_error < unpadded_error, "Padded error ({padded_error}) should be less than unpadded error ({unpadded_error}).");
+        assert!(padded_error < 1.0);
+
+        // The padded analysis still detects the right note.
+        let data = generate_tone(&[A.frequency()], 0.2, 44_100);
+        let notes = get_notes_from_audio_data_with_config(&data, 0.2, &AnalysisConfig::default().with_zero_pad_factor(4)).unwrap();
+
+        assert_eq!(notes, vec![A]);
+    }
+
+    #[test]
+    fn test_analysis_config() {
+        let data = generate_tone(&[CThree.frequency(), A.frequency()], 1.0, 44_100);
+        let notes = |config: AnalysisConfig| get_notes_from_audio_data_with_config(&data, 1.0, &config).unwrap();
+
+        assert_eq!(notes(AnalysisConfig::default()), vec![CThree, A]);
+
+        // The frequency range drops the peaks outside of it.
+        assert_eq!(notes(AnalysisConfig::default().with_frequency_range(200.0, 8_000.0)), vec![A]);
+        assert_eq!(notes(AnalysisConfig::default().with_frequency_range(80.0, 200.0)), vec![CThree]);
+
+        // A higher magnitude threshold drops weaker peaks.
+        assert_eq!(notes(AnalysisConfig::default().with_magnitude_threshold(f32::MAX)), vec![]);
+
+        // Windowing keeps the notes.
+        for window in [WindowFunction::Hann, WindowFunction::Hamming, WindowFunction::Blackman] {
+            assert_eq!(notes(AnalysisConfig::default().with_window(window)), vec![CThree, A]);
+        }
+    }
+
+    #[test]
+    fn test_window_function() {
+        assert_eq!(WindowFunction::Rectangular.apply(&[1.0; 5]), vec![1.0; 5]);
+
+        let hann = WindowFunction::Hann.apply(&[1.0; 5]);
+        assert!(hann[0].abs() < 1e-6 && hann[4].abs() < 1e-6);
+        assert!((hann[2] - 1.0).abs() < 1e-6);
+
+        let hamming = WindowFunction::Hamming.apply(&[1.0; 5]);
+        assert!((hamming[0] - 0.08).abs() < 1e-6);
+
+        assert_eq!(WindowFunction::Blackman.apply(&[0.5]), vec![0.5]);
+    }
+
+    #[test]
+    fn test_notes_from_spectrum() {
+        use crate::core::{base::Parsable, chord::Chord, note::*};
+
+        // Deliberately non-uniform, with a bit of noise.
+        let mut spectrum = vec![(0.0, 0.001), (55.5, 0.002), (140.25, 0.001), (1000.0, 0.002), (2500.0, 0.001)];
+        spectrum.push((C.frequency(), 10.0));
+        spectrum.push((E.frequency(), 8.0));
+        spectrum.push((G.frequency(), 9.0));
+
+        let mut notes = notes_from_spectrum(&spectrum, &AnalysisConfig::default());
+        notes.sort();
+
+        assert_eq!(notes, vec![C, E, G]);
+        assert_eq!(Chord::try_from_notes(&notes).unwrap()[0], Chord::parse("C").unwrap());
+
+        assert_eq!(notes_from_spectrum(&[], &AnalysisConfig::default()), vec![]);
     }
 
     #[test]
@@ -424,4 +1158,179 @@ pub(crate) mod tests {
     fn test_binary_search_closest_empty() {
         binary_search_closest(&[], 0.0, |x| *x).unwrap();
     }
+
+    #[test]
+    fn test_detect_chord_with_inversion() {
+        // C major, with the third in the bass.
+        let data = generate_tone(&[EThree.frequency(), GThree.frequency(), C.frequency()], 1.0, 44_100);
+        let (chord, inversion) = detect_chord_with_inversion(&data, 44100).unwrap();
+
+        assert_eq!(chord.root().pitch(), C.pitch());
+        assert_eq!(chord.known_chord(), KnownChord::Major);
+        assert_eq!(chord.chord()[0].pitch(), E.pitch());
+        assert_eq!(inversion, Inversion::First);
+
+        // Root position, and second inversion.
+        let data = generate_tone(&[CThree.frequency(), EThree.frequency(), GThree.frequency()], 1.0, 44_100);
+        assert_eq!(detect_chord_with_inversion(&data, 44100).unwrap().1, Inversion::Root);
+
+        let data = generate_tone(&[GThree.frequency(), C.frequency(), E.frequency()], 1.0, 44_100);
+        assert_eq!(detect_chord_with_inversion(&data, 44100).unwrap().1, Inversion::Second);
+    }
+
+    #[test]
+    fn test_flatness_gate() {
+        let config = AnalysisConfig::default().with_flatness_threshold(DEFAULT_FLATNESS_THRESHOLD);
+
+        // Deterministic white noise.
+        let mut seed = 42u32;
+        let noise = (0..44_100)
+            .map(|_| {
+                seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+
+                (seed >> 16) as f32 / 32_768.0 - 1.0
+            })
+            .collect::<Vec<_>>();
+        let tone = generate_tone(&[C.frequency(), E.frequency(), A.frequency()], 1.0, 44_100);
+
+        assert!(spectral_flatness(&get_frequency_space(&noise, 1.0)) > DEFAULT_FLATNESS_THRESHOLD);
+        assert!(spectral_flatness(&get_frequency_space(&tone, 1.0)) < 0.01);
+
+        assert_eq!(get_notes_from_audio_data_with_config(&noise, 1.0, &config).unwrap(), vec![]);
+        assert_eq!(get_notes_from_audio_data_with_config(&tone, 1.0, &config).unwrap(), vec![C, E, A]);
+
+        // Without the gate, the noise produces (spurious) notes.
+        assert!(!get_notes_from_audio_data(&noise, 1.0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_analysis_presets() {
+        let data = generate_tone(&[C.frequency(), E.frequency(), G.frequency()], 1.0, 44_100);
+
+        for preset in [AnalysisPreset::Fast, AnalysisPreset::Balanced, AnalysisPreset::Accurate] {
+            let config = AnalysisConfig::from(preset);
+            let chord = get_chord_from_audio_data_with_config(&data, 1.0, &config).unwrap().unwrap();
+
+            assert_eq!(chord.root().pitch(), C.pitch(), "{preset:?}");
+            assert_eq!(chord.known_chord(), KnownChord::Major, "{preset:?}");
+            assert!(preset.hop_in_seconds() <= preset.window_in_seconds());
+        }
+
+        assert!(AnalysisPreset::Accurate.config().fft_size(44_100) > AnalysisPreset::Fast.config().fft_size(44_100));
+        assert_eq!(AnalysisPreset::default().config(), AnalysisConfig::default());
+
+        // Each preset has its own window function, and its own overlap (i.e., none, half, and seven eighths).
+        let presets = [AnalysisPreset::Fast, AnalysisPreset::Balanced, AnalysisPreset::Accurate];
+
+        assert_eq!(presets.map(|p| p.config().window), [WindowFunction::Hamming, WindowFunction::Rectangular, WindowFunction::Hann]);
+        assert_eq!(presets.map(|p| 1.0 - p.hop_in_seconds() / p.window_in_seconds()), [0.0, 0.5, 0.875]);
+    }
+
+    #[test]
+    fn test_get_notes_with_frequencies_from_audio_data() {
+        // A slightly sharp A, along with an in-tune C and E.
+        let data = generate_tone(&[C.frequency(), E.frequency(), 443.6], 1.0, 44_100);
+        let result = get_notes_with_frequencies_from_audio_data(&data, 1.0).unwrap();
+
+        assert_eq!(result.iter().map(|(_, n)| *n).collect::<Vec<_>>(), vec![C, E, A]);
+
+        // Each frequency is within a quarter tone of the note it was mapped to.
+        for (frequency, note) in &result {
+            let cents = 1200.0 * (frequency / note.frequency()).log2();
+
+            assert!(cents.abs() < 50.0, "{frequency} Hz is {cents} cents from {}", note.frequency());
+        }
+
+        // The interpolated frequency is finer than the (1 Hz) bins.
+        assert!((result[2].0 - 443.6).abs() < 0.25, "{}", result[2].0);
+    }
+
+    #[test]
+    fn test_get_notes_from_silent_audio_data() {
+        assert_eq!(get_notes_from_audio_data(&[], 1.0).unwrap(), vec![]);
+        assert_eq!(get_notes_from_audio_data(&[0.0; 44_100], 1.0).unwrap(), vec![]);
+        assert_eq!(get_notes_with_frequencies_from_audio_data(&[0.0; 44_100], 1.0).unwrap(), vec![]);
+        assert_eq!(get_chord_from_audio_data_with_config(&[0.0; 44_100], 1.0, &AnalysisConfig::default()).unwrap(), None);
+        assert_eq!(detect_chord_with_inversion(&[0.0; 44_100], 44_100), None);
+        assert_eq!(
+            get_chord_from_audio_data_with_config(&[], 1.0, &AnalysisConfig::default().with_backend(Backend::ChromaTemplate)).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_measure_detection_latency() {
+        let data = generate_tone(&[A.frequency()], 1.0, 44_100);
+
+        // Take the median of several passes, to smooth over scheduling noise.
+        let latency = |config: &AnalysisConfig| {
+            let mut latencies = (0..5).map(|_| measure_detection_latency(&data, 44_100, config)).collect::<Vec<_>>();
+            latencies.sort();
+
+            latencies[latencies.len() / 2]
+        };
+
+        let small = latency(&AnalysisConfig::default());
+        let large = latency(&AnalysisConfig::default().with_zero_pad_factor(16));
+
+        // A 16 times larger FFT is slower, by a comfortable margin.
+        assert!(small > Duration::ZERO);
+        assert!(large > small * 2, "{large:?} should be more than twice {small:?}");
+    }
+
+    #[test]
+    fn test_min_window_for_frequency() {
+        let low_e = min_window_for_frequency(41.2, 44_100.0);
+        let a = min_window_for_frequency(A.frequency(), 44_100.0);
+
+        assert!(low_e > a);
+        assert!(low_e.is_power_of_two() && a.is_power_of_two());
+        assert!(low_e as f32 / 44_100.0 >= MIN_PERIODS_PER_WINDOW / 41.2);
+        assert_eq!(a, 512);
+
+        assert_eq!(min_window_for_frequency(0.0, 44_100.0), 0);
+    }
+
+    #[test]
+    fn test_get_notes_with_confidence_from_audio_data() {
+        // A loud C, and a quieter A.
+        let tone = |length_in_seconds: f32| {
+            (0..(length_in_seconds * 44_100.0) as usize)
+                .map(|k| {
+                    let t = k as f32 / 44_100.0;
+
+                    0.4 * (2.0 * PI * C.frequency() * t).sin() + 0.1 * (2.0 * PI * A.frequency() * t).sin()
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let short = get_notes_with_confidence_from_audio_data(&tone(0.5), 0.5).unwrap();
+        let long = get_notes_with_confidence_from_audio_data(&tone(2.0), 2.0).unwrap();
+
+        assert_eq!(short.iter().map(|(n, _)| *n).collect::<Vec<_>>(), vec![C, A]);
+        assert_eq!(short[0].1, 1.0);
+        assert!(short[1].1 < 0.5, "{}", short[1].1);
+
+        // The confidences are normalized, so they do not depend on the length of the clip.
+        assert_eq!(long.iter().map(|(n, _)| *n).collect::<Vec<_>>(), vec![C, A]);
+        assert!((long[1].1 - short[1].1).abs() < 0.1, "{} vs. {}", long[1].1, short[1].1);
+
+        // Dropping the confidences gives the plain notes.
+        let mut notes = long.into_iter().map(|(n, _)| n).collect::<Vec<_>>();
+        notes.sort();
+        assert_eq!(notes, get_notes_from_audio_data(&tone(2.0), 2.0).unwrap());
+    }
+
+    #[test]
+    fn test_get_notes_with_tuning() {
+        // A baroque A (415 Hz) is an A♭ in the standard tuning, but an A when tuned to it.
+        let data = generate_tone(&[415.0], 1.0, 44_100);
+
+        assert_eq!(get_notes_from_audio_data(&data, 1.0).unwrap(), vec![AFlat]);
+
+        with_tuning(TuningContext::new(415.0), || {
+            assert_eq!(A.frequency(), 415.0);
+            assert_eq!(get_notes_from_audio_data(&data, 1.0).unwrap(), vec![A]);
+        });
+    }
 }
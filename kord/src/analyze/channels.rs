@@ -0,0 +1,337 @@
+//! Multichannel audio analysis.
+//!
+//! Combines the spectra of the channels into a single spectrum before note detection.  Callers describe the layout of their
+//! buffers with a [`ChannelLayout`], so that surround and planar data are downmixed correctly.
+
+use crate::core::{
+    base::{KordError, Res},
+    note::Note,
+};
+
+use super::base::{get_frequency_space, get_notes_from_smoothed_frequency_space, get_smoothed_frequency_space, is_silent, sanitize_audio_data};
+
+// Enum.
+
+/// The way in which the channels of multichannel audio are combined before analysis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Downmix {
+    /// Averages the magnitudes of each frequency bin across the channels.
+    #[default]
+    Mean,
+    /// Takes the median magnitude of each frequency bin across the channels.
+    ///
+    /// This rejects noise that is only present in a minority of the channels (e.g., redundant mics where one picks up
+    /// a noisy source), rather than spreading it into the result like [`Downmix::Mean`] does.
+    MedianRobust,
+}
+
+/// The way in which the samples of the channels are arranged in a buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SampleArrangement {
+    /// The samples of each frame are stored together (e.g., `L R L R ...`).
+    #[default]
+    Interleaved,
+    /// The samples of each channel are stored together, one channel after the other (e.g., `L L ... R R ...`).
+    Planar,
+}
+
+/// The speaker position of a channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Channel {
+    /// A channel with no particular position (e.g., one of several redundant mics).
+    #[default]
+    Unspecified,
+    /// The front left channel.
+    FrontLeft,
+    /// The front right channel.
+    FrontRight,
+    /// The front center channel.
+    FrontCenter,
+    /// The low-frequency effects channel (i.e., the ".1" in 5.1).
+    LowFrequency,
+    /// The surround (side or back) left channel.
+    SurroundLeft,
+    /// The surround (side or back) right channel.
+    SurroundRight,
+}
+
+impl Channel {
+    /// Returns the weight of this channel when downmixing to mono.
+    ///
+    /// The center and surround channels are attenuated by 3 dB, and the low-frequency effects channel is dropped, since
+    /// it does not carry any harmonic content.
+    pub fn mono_weight(&self) -> f32 {
+        match self {
+            Channel::Unspecified | Channel::FrontLeft | Channel::FrontRight => 1.0,
+            Channel::FrontCenter | Channel::SurroundLeft | Channel::SurroundRight => std::f32::consts::FRAC_1_SQRT_2,
+            Channel::LowFrequency => 0.0,
+        }
+    }
+}
+
+// Struct.
+
+/// A description of the channels of an audio buffer: their order, and how their samples are arranged.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct ChannelLayout {
+    /// The channels, in the order they appear in the buffer.
+    pub channels: Vec<Channel>,
+    /// The arrangement of the samples in the buffer.
+    pub arrangement: SampleArrangement,
+}
+
+impl ChannelLayout {
+    /// Creates a new channel layout.
+    pub fn new(channels: Vec<Channel>, arrangement: SampleArrangement) -> Self {
+        Self { channels, arrangement }
+    }
+
+    /// Creates an interleaved layout of the given number of [`Channel::Unspecified`] channels.
+    pub fn interleaved(num_channels: usize) -> Self {
+        Self::new(vec![Channel::Unspecified; num_channels], SampleArrangement::Interleaved)
+    }
+
+    /// Creates an interleaved mono layout.
+    pub fn mono() -> Self {
+        Self::interleaved(1)
+    }
+
+    /// Creates an interleaved stereo layout (left, right).
+    pub fn stereo() -> Self {
+        Self::new(vec![Channel::FrontLeft, Channel::FrontRight], SampleArrangement::Interleaved)
+    }
+
+    /// Creates an interleaved 5.1 layout, in the usual (WAV / SMPTE) order: front left, front right, center, low-frequency
+    /// effects, surround left, surround right.
+    pub fn surround_5_1() -> Self {
+        Self::new(
+            vec![
+                Channel::FrontLeft,
+                Channel::FrontRight,
+                Channel::FrontCenter,
+                Channel::LowFrequency,
+                Channel::SurroundLeft,
+                Channel::SurroundRight,
+            ],
+            SampleArrangement::Interleaved,
+        )
+    }
+
+    /// Sets the sample arrangement of the layout, and returns a new layout.
+    pub fn with_arrangement(self, arrangement: SampleArrangement) -> Self {
+        Self { arrangement, ..self }
+    }
+
+    /// Returns the number of channels.
+    pub fn num_channels(&self) -> usize {
+        self.channels.len()
+    }
+
+    /// Splits the buffer into one vector of samples per channel.
+    ///
+    /// Errors if the layout has no channels, or if the buffer does not hold a whole number of frames.
+    pub fn split(&self, data: &[f32]) -> Res<Vec<Vec<f32>>> {
+        let num_channels = self.num_channels();
+
+        if num_channels == 0 {
+            return Err(KordError::InvalidArgument("Number of channels must be greater than 0.".to_string()).into());
+        }
+
+        if data.len() % num_channels != 0 {
+            return Err(KordError::InvalidArgument(format!("Buffer of {} samples does not hold a whole number of {num_channels}-channel frames.", data.len())).into());
+        }
+
+        let num_frames = data.len() / num_channels;
+
+        let result = (0..num_channels)
+            .map(|c| match self.arrangement {
+                SampleArrangement::Interleaved => data.iter().skip(c).step_by(num_channels).copied().collect(),
+                SampleArrangement::Planar => data[c * num_frames..(c + 1) * num_frames].to_vec(),
+            })
+            .collect();
+
+        Ok(result)
+    }
+
+    /// Downmixes the buffer to mono, weighting each channel by its [`Channel::mono_weight`].
+    ///
+    /// The weights are normalized, so that a signal that is present on every (weighted) channel keeps its level.
+    pub fn downmix_to_mono(&self, data: &[f32]) -> Res<Vec<f32>> {
+        let channels = self.split(data)?;
+        let weights = self.normalized_weights()?;
+
+        let result = (0..data.len() / channels.len()).map(|k| channels.iter().zip(&weights).map(|(c, w)| c[k] * w).sum()).collect();
+
+        Ok(result)
+    }
+
+    fn normalized_weights(&self) -> Res<Vec<f32>> {
+        let weights = self.channels.iter().map(|c| c.mono_weight()).collect::<Vec<_>>();
+        let total = weights.iter().sum::<f32>();
+
+        if total <= 0.0 {
+            return Err(KordError::InvalidArgument("Channel layout has no channels that carry harmonic content.".to_string()).into());
+        }
+
+        Ok(weights.into_iter().map(|w| w / total).collect())
+    }
+}
+
+// Functions.
+
+/// Gets the frequency space of interleaved multichannel audio data, combining the channels with the given [`Downmix`].
+///
+/// The `length_in_seconds` is the duration of the audio (i.e., of each channel).
+pub fn get_downmixed_frequency_space(data: &[f32], num_channels: usize, length_in_seconds: f32, downmix: Downmix) -> Res<Vec<(f32, f32)>> {
+    get_downmixed_frequency_space_with_layout(data, &ChannelLayout::interleaved(num_channels), length_in_seconds, downmix)
+}
+
+/// Gets the frequency space of multichannel audio data with the given [`ChannelLayout`], combining the channels with the
+/// given [`Downmix`].
+///
+/// The [`Downmix::Mean`] is weighted by each channel's [`Channel::mono_weight`], and the [`Downmix::MedianRobust`] only
+/// considers the channels with a non-zero weight.  The `length_in_seconds` is the duration of the audio (i.e., of each channel).
+pub fn get_downmixed_frequency_space_with_layout(data: &[f32], layout: &ChannelLayout, length_in_seconds: f32, downmix: Downmix) -> Res<Vec<(f32, f32)>> {
+    let channels = layout.split(&sanitize_audio_data(data))?;
+    let weights = layout.normalized_weights()?;
+
+    let (channel_spaces, weights): (Vec<_>, Vec<_>) = channels
+        .into_iter()
+        .zip(weights)
+        .filter(|(_, w)| *w > 0.0)
+        .map(|(channel, w)| (get_frequency_space(&channel, length_in_seconds), w))
+        .unzip();
+
+    let num_bins = channel_spaces.iter().map(|s| s.len()).min().unwrap_or(0);
+
+    let result = (0..num_bins)
+        .map(|k| {
+            let frequency = channel_spaces[0][k].0;
+            let mut magnitudes = channel_spaces.iter().map(|s| s[k].1).collect::<Vec<_>>();
+
+            let magnitude = match downmix {
+                Downmix::Mean => magnitudes.iter().zip(&weights).map(|(m, w)| m * w).sum::<f32>(),
+                Downmix::MedianRobust => {
+                    magnitudes.sort_by(|a, b| a.total_cmp(b));
+
+                    let mid = magnitudes.len() / 2;
+
+                    if magnitudes.len() % 2 == 0 {
+                        (magnitudes[mid - 1] + magnitudes[mid]) / 2.0
+                    } else {
+                        magnitudes[mid]
+                    }
+                }
+            };
+
+            (frequency, magnitude)
+        })
+        .collect();
+
+    Ok(result)
+}
+
+/// Gets notes from interleaved multichannel audio data, combining the channels with the given [`Downmix`].
+pub fn get_notes_from_multichannel_audio_data(data: &[f32], num_channels: usize, length_in_seconds: f32, downmix: Downmix) -> Res<Vec<Note>> {
+    get_notes_from_multichannel_audio_data_with_layout(data, &ChannelLayout::interleaved(num_channels), length_in_seconds, downmix)
+}
+
+/// Gets notes from multichannel audio data with the given [`ChannelLayout`], combining the channels with the given [`Downmix`].
+pub fn get_notes_from_multichannel_audio_data_with_layout(data: &[f32], layout: &ChannelLayout, length_in_seconds: f32, downmix: Downmix) -> Res<Vec<Note>> {
+    if length_in_seconds < 0.2 {
+        return Err(KordError::ListeningLengthTooShort.into());
+    }
+
+    if is_silent(data) {
+        return Ok(Vec::new());
+    }
+
+    let frequency_space = get_downmixed_frequency_space_with_layout(data, layout, length_in_seconds, downmix)?;
+    let smoothed_frequency_space = get_smoothed_frequency_space(&frequency_space, length_in_seconds);
+
+    Ok(get_notes_from_smoothed_frequency_space(&smoothed_frequency_space, length_in_seconds))
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{
+        helpers::generate_tone,
+        note::{EFlat, A, C},
+        pitch::HasFrequency,
+    };
+    use pretty_assertions::assert_eq;
+
+    fn interleave(channels: &[Vec<f32>]) -> Vec<f32> {
+        (0..channels[0].len()).flat_map(|k| channels.iter().map(move |c| c[k])).collect()
+    }
+
+    #[test]
+    fn test_median_robust_downmix() {
+        let clean = generate_tone(&[A.frequency()], 1.0, 44_100);
+
+        // One of the three mics picks up (deterministic) broadband noise, and bleed from another source.
+        let bleed = generate_tone(&[EFlat.frequency()], 1.0, 44_100);
+        let mut seed = 12345u32;
+        let noisy = clean
+            .iter()
+            .zip(bleed)
+            .map(|(s, b)| {
+                seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+
+                s + b * 2.0 + ((seed >> 16) as f32 / 32_768.0 - 1.0) * 0.5
+            })
+            .collect::<Vec<_>>();
+
+        let data = interleave(&[clean.clone(), clean, noisy]);
+
+        let mean = get_notes_from_multichannel_audio_data(&data, 3, 1.0, Downmix::Mean).unwrap();
+        let median = get_notes_from_multichannel_audio_data(&data, 3, 1.0, Downmix::MedianRobust).unwrap();
+
+        assert_eq!(median, vec![A]);
+        assert!(mean.contains(&EFlat), "Mean downmix detected {mean:?}.");
+    }
+
+    #[test]
+    fn test_downmix_no_channels() {
+        assert!(get_downmixed_frequency_space(&[0.0; 100], 0, 1.0, Downmix::Mean).is_err());
+    }
+
+    #[test]
+    fn test_surround_downmix() {
+        let front = generate_tone(&[A.frequency()], 1.0, 44_100);
+        let center = generate_tone(&[C.frequency()], 1.0, 44_100);
+        let rumble = generate_tone(&[EFlat.frequency()], 1.0, 44_100);
+        let silence = vec![0.0; front.len()];
+
+        // The low-frequency effects channel is loud, but should not leak into the downmix.
+        let lfe = rumble.iter().map(|s| s * 4.0).collect::<Vec<_>>();
+        let channels = [front.clone(), front.clone(), center.clone(), lfe, silence.clone(), silence];
+
+        let layout = ChannelLayout::surround_5_1();
+        let data = interleave(&channels);
+
+        let mono = layout.downmix_to_mono(&data).unwrap();
+        let center_weight = std::f32::consts::FRAC_1_SQRT_2;
+        let total = 2.0 + 3.0 * center_weight;
+
+        assert_eq!(mono.len(), front.len());
+        for k in [0, 1_000, 20_000] {
+            assert!((mono[k] - (2.0 * front[k] + center_weight * center[k]) / total).abs() < 1e-6);
+        }
+
+        let notes = get_notes_from_multichannel_audio_data_with_layout(&data, &layout, 1.0, Downmix::Mean).unwrap();
+        assert_eq!(notes, vec![C, A]);
+
+        // The same buffer, stored planar, downmixes identically.
+        let planar = channels.concat();
+        let planar_layout = ChannelLayout::surround_5_1().with_arrangement(SampleArrangement::Planar);
+
+        assert_eq!(planar_layout.downmix_to_mono(&planar).unwrap(), mono);
+
+        // A buffer that does not hold whole frames is rejected, rather than silently misaligned.
+        assert!(layout.downmix_to_mono(&data[1..]).is_err());
+    }
+}
@@ -0,0 +1,310 @@
+//! Chroma-based chord detection and key estimation.
+//!
+//! Folds the spectrum into a 12-bin chroma vector (the energy of each pitch class), and correlates it against binary
+//! templates of each chord type, or (accumulated over time) against key profiles.
+
+use crate::core::{
+    chord::{Chord, Chordable, HasChord},
+    note::Note,
+    pitch::{tuning, HasPitch, ALL_PITCHES},
+};
+
+use super::base::{get_frequency_space, sanitize_audio_data};
+
+// Statics.
+
+/// The lowest frequency that contributes to the chroma vector.
+static CHROMA_MIN_FREQUENCY: f32 = 60.0;
+
+/// The highest frequency that contributes to the chroma vector.
+static CHROMA_MAX_FREQUENCY: f32 = 4_200.0;
+
+/// The Krumhansl-Kessler major key profile (i.e., the perceived stability of each scale degree, starting from the tonic).
+static MAJOR_KEY_PROFILE: [f32; 12] = [6.35, 2.23, 3.48, 2.33, 4.38, 4.09, 2.52, 5.19, 2.39, 3.66, 2.29, 2.88];
+
+/// The Krumhansl-Kessler minor key profile (i.e., the perceived stability of each scale degree, starting from the tonic).
+static MINOR_KEY_PROFILE: [f32; 12] = [6.33, 2.68, 3.52, 5.38, 2.60, 3.53, 2.54, 4.75, 3.98, 2.69, 3.34, 3.17];
+
+// Struct.
+
+/// An online key estimator, which keeps a running estimate of the key as audio windows arrive.
+///
+/// The estimator accumulates a chroma histogram of every window it is given, and correlates it against the major and minor
+/// key profiles of each tonic (the Krumhansl-Schmuckler algorithm), so the estimate stabilizes as more of the song is heard.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct KeyEstimator {
+    histogram: [f32; 12],
+    num_windows: usize,
+}
+
+impl KeyEstimator {
+    /// Creates a new (empty) key estimator.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a window of audio data to the estimate.
+    pub fn push_window(&mut self, data: &[f32], length_in_seconds: f32) {
+        self.push_chroma(&get_chroma(&sanitize_audio_data(data), length_in_seconds));
+    }
+
+    /// Adds a (pre-computed) chroma vector to the estimate.
+    ///
+    /// Silent chroma vectors are ignored.
+    pub fn push_chroma(&mut self, chroma: &[f32; 12]) {
+        if chroma.iter().all(|c| *c <= 0.0) {
+            return;
+        }
+
+        for (total, value) in self.histogram.iter_mut().zip(chroma) {
+            *total += value;
+        }
+
+        self.num_windows += 1;
+    }
+
+    /// Returns the number of (non-silent) windows that contributed to the estimate.
+    pub fn num_windows(&self) -> usize {
+        self.num_windows
+    }
+
+    /// Clears the estimate (e.g., when a new song starts).
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Returns the current key estimate as (tonic, is minor), or `None` if no audio has been heard yet.
+    pub fn current_key(&self) -> Option<(Note, bool)> {
+        if self.num_windows == 0 {
+            return None;
+        }
+
+        let mut best: Option<((Note, bool), f32)> = None;
+
+        for (tonic, pitch) in ALL_PITCHES.iter().enumerate() {
+            for (is_minor, profile) in [(false, &MAJOR_KEY_PROFILE), (true, &MINOR_KEY_PROFILE)] {
+                let rotated = (0..12).map(|k| profile[(k + 12 - tonic) % 12]).collect::<Vec<_>>();
+                let score = correlation(&self.histogram, &rotated);
+
+                if best.as_ref().map(|(_, best_score)| score > *best_score).unwrap_or(true) {
+                    best = Some(((Note::from_pitch(*pitch, 4), is_minor), score));
+                }
+            }
+        }
+
+        best.map(|(key, _)| key)
+    }
+}
+
+// Functions.
+
+/// Computes the chroma vector of the audio data (i.e., the energy of each pitch class, starting from C).
+///
+/// The vector is normalized so that its largest element is `1` (unless the audio is silent, in which case it is all `0`).
+pub fn get_chroma(data: &[f32], length_in_seconds: f32) -> [f32; 12] {
+    let mut chroma = [0.0f32; 12];
+    let tuning = tuning();
+
+    for (frequency, magnitude) in get_frequency_space(data, length_in_seconds) {
+        if !(CHROMA_MIN_FREQUENCY..=CHROMA_MAX_FREQUENCY).contains(&frequency) {
+            continue;
+        }
+
+        // A4 is nine semitones above C4.
+        let semitones_from_a = tuning.semitones_from_a4(frequency);
+        let pitch_class = (semitones_from_a.round() as i32 + 9).rem_euclid(12) as usize;
+
+        chroma[pitch_class] += magnitude * magnitude;
+    }
+
+    let max = chroma.iter().copied().fold(0.0, f32::max);
+
+    if max > 0.0 {
+        for value in &mut chroma {
+            *value /= max;
+        }
+    }
+
+    chroma
+}
+
+/// Gets the chord whose template best correlates with the given chroma vector.
+///
+/// Returns `None` if the chroma vector is silent.  Ties are broken in favor of the simpler chord type.
+pub fn get_chord_from_chroma(chroma: &[f32; 12]) -> Option<Chord> {
+    get_chord_from_chroma_with_prior(chroma, None, 0.0)
+}
+
+/// Gets the chord whose template best correlates with the given chroma vector, biased toward the previous chord.
+///
+/// Candidates that are diatonically related to `previous` (i.e., both chords fit in a single major scale) have
+/// `prior_strength` added to their score, which smooths detection over a progression.  Returns `None` if the chroma
+/// vector is silent.
+pub fn get_chord_from_chroma_with_prior(chroma: &[f32; 12], previous: Option<&Chord>, prior_strength: f32) -> Option<Chord> {
+    let chroma_norm = chroma.iter().map(|c| c * c).sum::<f32>().sqrt();
+
+    if chroma_norm <= 0.0 {
+        return None;
+    }
+
+    let mut best: Option<(Chord, f32)> = None;
+
+    for (chord, template) in chord_templates() {
+        let dot = chroma.iter().zip(template).map(|(c, t)| c * t).sum::<f32>();
+        let template_norm = template.iter().map(|t| t * t).sum::<f32>().sqrt();
+        let mut score = dot / (chroma_norm * template_norm);
+
+        if let Some(previous) = previous {
+            if prior_strength > 0.0 && are_diatonically_related(previous, &chord) {
+                score += prior_strength;
+            }
+        }
+
+        if best.as_ref().map(|(_, best_score)| score > *best_score).unwrap_or(true) {
+            best = Some((chord, score));
+        }
+    }
+
+    best.map(|(chord, _)| chord)
+}
+
+// Helpers.
+
+/// Returns the Pearson correlation of the two vectors (or `0` if either is constant).
+fn correlation(a: &[f32], b: &[f32]) -> f32 {
+    let mean_a = a.iter().sum::<f32>() / a.len() as f32;
+    let mean_b = b.iter().sum::<f32>() / b.len() as f32;
+
+    let covariance = a.iter().zip(b).map(|(x, y)| (x - mean_a) * (y - mean_b)).sum::<f32>();
+    let variance_a = a.iter().map(|x| (x - mean_a).powi(2)).sum::<f32>();
+    let variance_b = b.iter().map(|y| (y - mean_b).powi(2)).sum::<f32>();
+
+    if variance_a <= 0.0 || variance_b <= 0.0 {
+        return 0.0;
+    }
+
+    covariance / (variance_a * variance_b).sqrt()
+}
+
+/// Returns whether or not the two chords fit in a single major scale.
+fn are_diatonically_related(a: &Chord, b: &Chord) -> bool {
+    const MAJOR_SCALE: [u8; 7] = [0, 2, 4, 5, 7, 9, 11];
+
+    let pitch_classes = a.pitch_class_set().into_iter().chain(b.pitch_class_set()).collect::<Vec<_>>();
+
+    (0..12).any(|tonic| pitch_classes.iter().all(|pc| MAJOR_SCALE.contains(&((pc + 12 - tonic) % 12))))
+}
+
+/// Builds the binary templates of each supported chord type, on each root.
+fn chord_templates() -> Vec<(Chord, [f32; 12])> {
+    let kinds: [fn(Chord) -> Chord; 9] = [
+        |c| c,
+        |c| c.minor(),
+        |c| c.diminished(),
+        |c| c.augmented(),
+        |c| c.seven(),
+        |c| c.major7(),
+        |c| c.minor().seven(),
+        |c| c.half_diminished(),
+        |c| c.sus4(),
+    ];
+
+    kinds
+        .iter()
+        .flat_map(|kind| {
+            ALL_PITCHES.iter().map(move |pitch| {
+                let chord = kind(Chord::new(Note::from_pitch(*pitch, 4)));
+
+                let mut template = [0.0f32; 12];
+                for note in chord.chord() {
+                    template[note.pitch() as usize] = 1.0;
+                }
+
+                (chord, template)
+            })
+        })
+        .collect()
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        analyze::base::{get_chord_from_audio_data_with_config, AnalysisConfig, Backend},
+        core::{
+            base::{HasName, Parsable},
+            helpers::generate_tone,
+            note::{A, G},
+            pitch::HasFrequency,
+        },
+    };
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_chroma_template() {
+        let config = AnalysisConfig::default().with_backend(Backend::ChromaTemplate);
+
+        for name in ["C", "Am", "G7", "Bm7(♭5)", "Fmaj7", "Dm7", "Esus4"] {
+            let expected = Chord::parse(name).unwrap();
+            let frequencies = expected.chord().iter().map(|n| n.frequency()).collect::<Vec<_>>();
+            let data = generate_tone(&frequencies, 1.0, 44_100);
+
+            let chord = get_chord_from_audio_data_with_config(&data, 1.0, &config).unwrap().unwrap();
+
+            assert_eq!(chord.name(), expected.name());
+        }
+    }
+
+    #[test]
+    fn test_key_estimator() {
+        let window = |chord: &str| {
+            let frequencies = Chord::parse(chord).unwrap().chord().iter().map(|n| n.frequency()).collect::<Vec<_>>();
+
+            generate_tone(&frequencies, 0.5, 44_100)
+        };
+
+        let mut estimator = KeyEstimator::new();
+        assert_eq!(estimator.current_key(), None);
+
+        // A I-vi-IV-V progression in G major, heard a couple of times.
+        for chord in ["G", "Em", "C", "D7", "G", "Em", "C", "D7", "G"] {
+            estimator.push_window(&window(chord), 0.5);
+        }
+
+        assert_eq!(estimator.num_windows(), 9);
+        assert_eq!(estimator.current_key(), Some((G, false)));
+
+        // A i-iv-V progression in A minor (with the raised leading tone).
+        estimator.reset();
+
+        for chord in ["Am", "Dm", "E7", "Am", "Dm", "E7", "Am"] {
+            estimator.push_window(&window(chord), 0.5);
+        }
+
+        assert_eq!(estimator.current_key(), Some((A, true)));
+
+        // Silence does not count.
+        estimator.push_window(&[0.0; 22_050], 0.5);
+        assert_eq!(estimator.num_windows(), 7);
+    }
+
+    #[test]
+    fn test_chroma_tuning() {
+        use crate::core::pitch::{with_tuning, TuningContext};
+
+        let loudest = |chroma: [f32; 12]| chroma.iter().position(|&value| value == 1.0).unwrap();
+        let data = generate_tone(&[415.0], 1.0, 44_100);
+
+        // A 415 Hz tone is an A♭ in the standard tuning, but an A when A4 is 415 Hz.
+        assert_eq!(loudest(get_chroma(&data, 1.0)), 8);
+        assert_eq!(with_tuning(TuningContext::new(415.0), || loudest(get_chroma(&data, 1.0))), 9);
+    }
+
+    #[test]
+    fn test_chroma_silence() {
+        assert_eq!(get_chroma(&[0.0; 44_100], 1.0), [0.0; 12]);
+        assert_eq!(get_chord_from_chroma(&[0.0; 12]), None);
+    }
+}
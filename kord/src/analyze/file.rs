@@ -12,7 +12,7 @@ use rodio::{buffer::SamplesBuffer, Decoder, OutputStream, Source};
 
 use crate::core::{base::Res, note::Note};
 
-use super::base::get_notes_from_audio_data;
+use super::{base::get_notes_from_audio_data, channels::ChannelLayout};
 
 /// Retrieve a list of notes which are guessed from the given audio clip.
 pub fn get_notes_from_audio_file(file: impl AsRef<Path>, start: Option<Duration>, end: Option<Duration>) -> Res<Vec<Note>> {
@@ -21,23 +21,31 @@ pub fn get_notes_from_audio_file(file: impl AsRef<Path>, start: Option<Duration>
     get_notes_from_audio_data(&data, length_in_seconds)
 }
 
-/// Gets the audio data from a file.
+/// Retrieve a list of notes which are guessed from the whole of the given audio file.
+pub fn get_notes_from_file<P: AsRef<Path>>(path: P) -> Res<Vec<Note>> {
+    get_notes_from_audio_file(path, None, None)
+}
+
+/// Gets the audio data from a file, downmixed to mono, along with its length in seconds.
+///
+/// The channels are averaged, and the data keeps the file's sample rate (which is implied by the length, so files that are
+/// not sampled at 44.1 kHz are analyzed correctly).
 pub fn get_audio_data_from_file(file: impl AsRef<Path>, start: Option<Duration>, end: Option<Duration>) -> Res<(Vec<f32>, f32)> {
     let path = file.as_ref();
     let start = start.unwrap_or_default();
 
     let decoder = Decoder::new(File::open(path)?)?.skip_duration(start).convert_samples();
 
-    let num_channels = decoder.channels();
+    let num_channels = decoder.channels() as usize;
     let sample_rate = decoder.sample_rate();
-    let samples: Vec<_> = if let Some(end) = end { decoder.take_duration(end - start).collect() } else { decoder.collect() };
+    let mut samples: Vec<_> = if let Some(end) = end { decoder.take_duration(end - start).collect() } else { decoder.collect() };
 
-    let num_samples = samples.len();
+    // Drop any trailing partial frame, and average the channels.
+    samples.truncate(samples.len() - samples.len() % num_channels.max(1));
 
-    let length_in_seconds = (num_samples as f32) / (sample_rate as f32 * num_channels as f32);
+    let data = ChannelLayout::interleaved(num_channels).downmix_to_mono(&samples)?;
 
-    // Cut the samples to the nearest second.
-    let data = samples[..(length_in_seconds * sample_rate as f32 * num_channels as f32) as usize].to_vec();
+    let length_in_seconds = data.len() as f32 / sample_rate as f32;
 
     Ok((data, length_in_seconds))
 }
@@ -98,6 +106,55 @@ mod tests {
         assert_eq!(Chord::parse("C7b9").unwrap(), Chord::try_from_notes(&notes).unwrap()[0]);
     }
 
+    #[cfg(feature = "analyze_file")]
+    #[test]
+    fn test_get_notes_from_stereo_file() {
+        use crate::core::{note::*, pitch::HasFrequency};
+        use std::{f32::consts::PI, io::Write};
+
+        // A one second, 48 kHz, 16-bit stereo WAV, with a C and an E on the left, and a G on the right.
+        let sample_rate = 48_000u32;
+        let tone = |notes: &[Note], k: u32| notes.iter().map(|n| (2.0 * PI * n.frequency() * k as f32 / sample_rate as f32).sin()).sum::<f32>() * 0.3;
+
+        let samples = (0..sample_rate)
+            .flat_map(|k| [tone(&[C, E], k), tone(&[G], k)])
+            .map(|s| (s * i16::MAX as f32) as i16)
+            .collect::<Vec<_>>();
+        let data_size = samples.len() as u32 * 2;
+
+        let mut bytes = Vec::new();
+        bytes.extend(b"RIFF");
+        bytes.extend((36 + data_size).to_le_bytes());
+        bytes.extend(b"WAVEfmt ");
+        bytes.extend(16u32.to_le_bytes());
+        bytes.extend(1u16.to_le_bytes());
+        bytes.extend(2u16.to_le_bytes());
+        bytes.extend(sample_rate.to_le_bytes());
+        bytes.extend((sample_rate * 4).to_le_bytes());
+        bytes.extend(4u16.to_le_bytes());
+        bytes.extend(16u16.to_le_bytes());
+        bytes.extend(b"data");
+        bytes.extend(data_size.to_le_bytes());
+        bytes.extend(samples.iter().flat_map(|s| s.to_le_bytes()));
+
+        // Use a unique file (so that concurrent runs do not collide), and clean it up before asserting.
+        let nanos = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_nanos();
+        let path = std::env::temp_dir().join(format!("kord_test_stereo_48k_{}_{nanos}.wav", std::process::id()));
+        File::create(&path).unwrap().write_all(&bytes).unwrap();
+
+        let audio_data = get_audio_data_from_file(&path, None, None);
+        let notes = get_notes_from_file(&path);
+
+        std::fs::remove_file(&path).unwrap();
+
+        let (data, length_in_seconds) = audio_data.unwrap();
+
+        assert_eq!(data.len(), sample_rate as usize);
+        assert_eq!(length_in_seconds, 1.0);
+
+        assert_eq!(notes.unwrap(), vec![C, E, G]);
+    }
+
     #[cfg(feature = "analyze_file")]
     #[cfg(feature = "analyze_file_mp3")]
     #[test]
@@ -1,123 +1,481 @@
 //! Analyzes audio data from the microphone.
 
 use std::{
-    sync::{Arc, Mutex},
-    time::Duration,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{channel, Receiver},
+        Arc, Mutex,
+    },
+    thread::JoinHandle,
+    time::{Duration, Instant},
 };
 
-use anyhow::Context;
 use cpal::{
     traits::{DeviceTrait, HostTrait, StreamTrait},
     InputCallbackInfo,
 };
+use rtrb::RingBuffer;
 
-use crate::core::{base::Res, note::Note};
+use crate::core::{
+    base::{KordError, Res},
+    note::Note,
+    pitch::{set_tuning, tuning},
+};
+
+use super::{base::analyze_samples, stream::note_stream_from_source};
+
+// Statics.
+
+/// How long a capture waits for the input device to deliver its first samples before giving up (see [`KordError::Timeout`]).
+pub static DEVICE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How often a capture checks whether it has been cancelled.
+static CANCELLATION_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+// Struct.
+
+/// A token that cooperatively cancels a microphone capture (see [`get_audio_data_from_microphone_with_cancellation`]).
+///
+/// Clones share the same state, so a clone can be handed to the capture, and another kept to cancel it (e.g., from a stop
+/// button).
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    is_cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Creates a new token, which is not cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels the token (and every capture that was given one of its clones).
+    pub fn cancel(&self) {
+        self.is_cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns whether or not the token has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.is_cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// A handle to a continuous microphone capture (see [`stream_notes_from_microphone`]).
+///
+/// Capture stops when the handle is dropped, and the detection thread finishes once it has analyzed the window that it is
+/// currently working on (if any).
+pub struct MicrophoneStream {
+    input: Option<cpal::Stream>,
+    detection: Option<JoinHandle<()>>,
+}
 
-use super::base::get_notes_from_audio_data;
+impl Drop for MicrophoneStream {
+    fn drop(&mut self) {
+        // Dropping the input stream drops the callback's sender, which ends the detection thread.
+        self.input.take();
+
+        if let Some(detection) = self.detection.take() {
+            let _ = detection.join();
+        }
+    }
+}
+
+// Functions.
 
 /// Gets notes from the microphone input over the specified period of time.
 pub async fn get_notes_from_microphone(length_in_seconds: f32) -> Res<Vec<Note>> {
+    get_notes_from_microphone_with_device(length_in_seconds, None).await
+}
+
+/// Gets notes from the input device with the given name (see [`list_input_devices`]), or the default input device if `None`,
+/// over the specified period of time.
+pub async fn get_notes_from_microphone_with_device(length_in_seconds: f32, device_name: Option<&str>) -> Res<Vec<Note>> {
     // Get data.
 
-    let data_from_microphone = get_audio_data_from_microphone(length_in_seconds).await?;
+    let (data_from_microphone, sample_rate, channels) = record_from_microphone(length_in_seconds, device_name, None).await?;
 
-    // Get notes.
+    // Get notes (from the device's actual sample rate, so that the frequencies do not depend on the recording's exact length).
 
-    let result = get_notes_from_audio_data(&data_from_microphone, length_in_seconds)?;
+    let data = downmix_to_mono(&data_from_microphone, channels);
+    let result = analyze_samples(&data, sample_rate, data.len() as f32 / sample_rate as f32)?;
 
     Ok(result)
 }
 
 /// Gets audio data from the microphone.
 pub async fn get_audio_data_from_microphone(length_in_seconds: f32) -> Res<Vec<f32>> {
-    if length_in_seconds < 0.2 {
-        return Err(anyhow::Error::msg("Listening length in seconds must be greater than 0.2."));
+    get_audio_data_from_microphone_with_device(length_in_seconds, None).await
+}
+
+/// Gets audio data from the input device with the given name (see [`list_input_devices`]), or the default input device if `None`.
+///
+/// Returns an error (which lists the available devices) if no input device has the given name.
+pub async fn get_audio_data_from_microphone_with_device(length_in_seconds: f32, device_name: Option<&str>) -> Res<Vec<f32>> {
+    let (data_from_microphone, _, _) = record_from_microphone(length_in_seconds, device_name, None).await?;
+
+    Ok(data_from_microphone)
+}
+
+/// Gets notes from the input device with the given name (or the default input device if `None`), over the specified period of
+/// time, or until the token is cancelled (in which case the notes are detected from the audio captured so far).
+///
+/// Returns [`KordError::Cancelled`] if the token is cancelled before any audio is captured.
+pub async fn get_notes_from_microphone_with_cancellation(length_in_seconds: f32, device_name: Option<&str>, cancellation: &CancellationToken) -> Res<Vec<Note>> {
+    let (data_from_microphone, sample_rate, channels) = record_from_microphone(length_in_seconds, device_name, Some(cancellation)).await?;
+
+    let data = downmix_to_mono(&data_from_microphone, channels);
+
+    analyze_samples(&data, sample_rate, data.len() as f32 / sample_rate as f32)
+}
+
+/// Gets audio data from the input device with the given name (or the default input device if `None`), over the specified period
+/// of time, or until the token is cancelled (in which case the audio captured so far is returned).
+///
+/// Returns [`KordError::Cancelled`] if the token is cancelled before any audio is captured, and [`KordError::Timeout`] if the
+/// device does not deliver any audio within [`DEVICE_TIMEOUT`] (or within the whole capture, if it is shorter).  Dropping the
+/// returned future also stops the capture.
+pub async fn get_audio_data_from_microphone_with_cancellation(length_in_seconds: f32, device_name: Option<&str>, cancellation: &CancellationToken) -> Res<Vec<f32>> {
+    let (data_from_microphone, _, _) = record_from_microphone(length_in_seconds, device_name, Some(cancellation)).await?;
+
+    Ok(data_from_microphone)
+}
+
+/// Lists the names of the available input devices (of the default host).
+pub fn list_input_devices() -> Res<Vec<String>> {
+    let host = cpal::default_host();
+
+    let devices = host.input_devices().map_err(|e| KordError::StreamError(format!("Could not enumerate input devices: {e}.")))?;
+
+    Ok(devices.filter_map(|device| device.name().ok()).collect())
+}
+
+/// Estimates the round-trip latency of the default output and input devices by playing a click, and recording it.
+///
+/// The result can be used with [`compensate_latency`](super::onset::compensate_latency) to align detected onsets with real time.
+#[coverage(off)]
+pub async fn calibrate_latency() -> Res<Duration> {
+    use rodio::{buffer::SamplesBuffer, OutputStream, Sink};
+
+    use super::onset::{estimate_latency, generate_click};
+
+    static CLICK_SAMPLE_RATE: u32 = 44100;
+    static CLICK_TIME_IN_SECONDS: f32 = 0.25;
+    static LENGTH_IN_SECONDS: f32 = 1.5;
+
+    // Set up devices and systems.
+
+    let (device, config) = get_device_and_config(None)?;
+    let sample_rate = config.sample_rate().0;
+    let channels = config.channels() as usize;
+
+    let (_stream, stream_handle) = OutputStream::try_default()?;
+    let sink = Sink::try_new(&stream_handle)?;
+
+    // Play the click (which is preceded by some silence), while recording.
+
+    sink.append(SamplesBuffer::new(1, CLICK_SAMPLE_RATE, generate_click(CLICK_SAMPLE_RATE, LENGTH_IN_SECONDS, CLICK_TIME_IN_SECONDS)));
+
+    let recorded = record_from_device(device, config, LENGTH_IN_SECONDS, None).await?;
+
+    // Only the first channel is needed to find the click.
+    let recorded = recorded.into_iter().step_by(channels.max(1)).collect::<Vec<_>>();
+
+    estimate_latency(&recorded, sample_rate, CLICK_TIME_IN_SECONDS).ok_or_else(|| anyhow::Error::msg("Could not detect the calibration click in the recording."))
+}
+
+/// Gets a [`Stream`](futures::Stream) of notes detected from continuous microphone input.
+///
+/// Each item is the set of notes detected in a window of `window_in_seconds` (downmixed to mono), and a new item is produced
+/// every `hop_in_seconds`.  Capture stops when the returned stream is dropped.
+#[coverage(off)]
+#[cfg(feature = "analyze_stream")]
+pub fn note_stream(window_in_seconds: f32, hop_in_seconds: f32) -> Res<impl futures::Stream<Item = Vec<Note>> + Unpin> {
+    use futures::channel::mpsc::unbounded;
+
+    use super::stream::NoteStream;
+
+    /// Keeps the input stream alive for as long as the note stream is being consumed.
+    struct MicrophoneNoteStream {
+        _input: cpal::Stream,
+        notes: NoteStream<futures::channel::mpsc::UnboundedReceiver<Vec<f32>>>,
+    }
+
+    impl futures::Stream for MicrophoneNoteStream {
+        type Item = Vec<Note>;
+
+        fn poll_next(mut self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> std::task::Poll<Option<Self::Item>> {
+            std::pin::Pin::new(&mut self.notes).poll_next(cx)
+        }
     }
 
     // Set up devices and systems.
 
-    let (device, config) = get_device_and_config()?;
+    let (device, config) = get_device_and_config(None)?;
+    let sample_rate = config.sample_rate().0;
+    let channels = (config.channels() as usize).max(1);
+
+    // Forward every captured chunk to the note stream (which downmixes it, and analyzes it at the device's sample rate).
+
+    let (sender, receiver) = unbounded();
+    let notes = note_stream_from_source(receiver, sample_rate, channels, window_in_seconds, hop_in_seconds)?;
+
+    let input = {
+        let error_sender = sender.clone();
+
+        device
+            .build_input_stream::<f32, _, _>(
+                &config.into(),
+                move |data: &[_], _: &InputCallbackInfo| {
+                    // The receiver is only gone once the note stream is dropped, so there is nobody left to notify.
+                    let _ = sender.unbounded_send(data.to_vec());
+                },
+                move |_| {
+                    error_sender.close_channel();
+                },
+                None,
+            )
+            .map_err(stream_error)?
+    };
 
-    // Record audio from the microphone.
+    input.play().map_err(stream_error)?;
 
-    let data_from_microphone = record_from_device(device, config, length_in_seconds).await?;
+    Ok(MicrophoneNoteStream { _input: input, notes })
+}
 
-    Ok(data_from_microphone)
+/// Continuously detects notes from the microphone input, over a sliding window.
+///
+/// Returns a [`MicrophoneStream`] handle, which keeps the capture alive, and a receiver of the notes detected in each window of
+/// `window_in_seconds`, with a new window every `hop_in_seconds`.  The input is downmixed to mono, and its windows span the
+/// boundaries between the device's callbacks, so that no audio is lost between windows.
+///
+/// Capture stops when the handle is dropped.  Dropping the receiver stops detection, but the capture callback keeps running
+/// (and discarding its samples) until the handle is dropped.
+#[coverage(off)]
+pub fn stream_notes_from_microphone(window_in_seconds: f32, hop_in_seconds: f32) -> Res<(MicrophoneStream, Receiver<Vec<Note>>)> {
+    // Set up devices and systems.
+
+    let (device, config) = get_device_and_config(None)?;
+    let sample_rate = config.sample_rate().0;
+    let channels = (config.channels() as usize).max(1);
+
+    // Forward every captured chunk to the detection thread.
+
+    let (sender, chunks) = channel();
+    let (detection, receiver) = spawn_window_detection(chunks, sample_rate, channels, window_in_seconds, hop_in_seconds)?;
+
+    let input = device
+        .build_input_stream::<f32, _, _>(
+            &config.into(),
+            move |data: &[_], _: &InputCallbackInfo| {
+                // The detection thread is only gone once the receiver is dropped, so there is nobody left to notify.
+                let _ = sender.send(data.to_vec());
+            },
+            |_| {},
+            None,
+        )
+        .map_err(stream_error)?;
+
+    input.play().map_err(stream_error)?;
+
+    let stream = MicrophoneStream {
+        input: Some(input),
+        detection: Some(detection),
+    };
+
+    Ok((stream, receiver))
 }
 
-/// Gets the system device, and config.
-fn get_device_and_config() -> Res<(cpal::Device, cpal::SupportedStreamConfig)> {
+/// Gets the system device (the input device with the given name, or the default input device if `None`), and config.
+fn get_device_and_config(device_name: Option<&str>) -> Res<(cpal::Device, cpal::SupportedStreamConfig)> {
     let host = cpal::default_host();
 
-    let device = host.default_input_device().ok_or_else(|| anyhow::Error::msg("Failed to get default input device."))?;
+    let device = match device_name {
+        Some(name) => {
+            let mut devices = host.input_devices().map_err(|e| KordError::StreamError(format!("Could not enumerate input devices: {e}.")))?;
+
+            match devices.find(|device| device.name().map(|n| n == name).unwrap_or(false)) {
+                Some(device) => device,
+                None => {
+                    let available = list_input_devices()?;
+
+                    return Err(KordError::NoInputDevice {
+                        requested: Some(name.to_string()),
+                        available,
+                    }
+                    .into());
+                }
+            }
+        }
+        None => host.default_input_device().ok_or_else(|| KordError::NoInputDevice { requested: None, available: Vec::new() })?,
+    };
 
-    let config = device.default_input_config().context("Could not get default input config.")?;
+    let config = device.default_input_config().map_err(|e| KordError::StreamError(format!("Could not get default input config: {e}.")))?;
 
     Ok((device, config))
 }
 
-/// Records audio data from the device.
-async fn record_from_device(device: cpal::Device, config: cpal::SupportedStreamConfig, length_in_seconds: f32) -> Res<Vec<f32>> {
+/// Spawns a thread that runs a [`NoteStream`](super::stream::NoteStream) over the interleaved chunks from `chunks` (of
+/// `channels` channels, sampled at `sample_rate`), and sends the notes detected in each window.
+///
+/// The thread finishes when the sending end of `chunks` is dropped, or when the returned receiver is dropped.
+fn spawn_window_detection(chunks: Receiver<Vec<f32>>, sample_rate: u32, channels: usize, window_in_seconds: f32, hop_in_seconds: f32) -> Res<(JoinHandle<()>, Receiver<Vec<Note>>)> {
+    let notes = note_stream_from_source(chunks.into_iter(), sample_rate, channels, window_in_seconds, hop_in_seconds)?;
+    let (sender, receiver) = channel();
+
+    // The tuning is per thread, so carry the caller's over to the detection thread.
+    let tuning = tuning();
+
+    let handle = std::thread::spawn(move || {
+        set_tuning(tuning);
+
+        for notes in notes {
+            if sender.send(notes).is_err() {
+                return;
+            }
+        }
+    });
+
+    Ok((handle, receiver))
+}
+
+/// Records (interleaved) audio data from the input device with the given name, or the default input device if `None`, along
+/// with the device's sample rate, and number of channels.
+async fn record_from_microphone(length_in_seconds: f32, device_name: Option<&str>, cancellation: Option<&CancellationToken>) -> Res<(Vec<f32>, u32, usize)> {
+    if length_in_seconds < 0.2 {
+        return Err(KordError::ListeningLengthTooShort.into());
+    }
+
+    // Set up devices and systems.
+
+    let (device, config) = get_device_and_config(device_name)?;
+    let sample_rate = config.sample_rate().0;
+    let channels = (config.channels() as usize).max(1);
+
+    // Record audio from the microphone.
+
+    let data_from_microphone = record_from_device(device, config, length_in_seconds, cancellation).await?;
+
+    Ok((data_from_microphone, sample_rate, channels))
+}
+
+/// Records audio data from the device, for the given length, or until the token (if any) is cancelled.
+///
+/// Recording stops with [`KordError::Timeout`] if the device does not deliver any audio within [`DEVICE_TIMEOUT`] (see
+/// [`wait_for_recording`]).
+async fn record_from_device(device: cpal::Device, config: cpal::SupportedStreamConfig, length_in_seconds: f32, cancellation: Option<&CancellationToken>) -> Res<Vec<f32>> {
     // Set up recording.
 
     let sample_rate = config.sample_rate().0 as f32;
     let channels = config.channels() as usize;
     let required_samples = (sample_rate * length_in_seconds) as usize * channels;
 
-    // let likely_sample_count = config.sample_rate().0 as f32 * config.channels() as f32 * length_in_seconds;
-
-    let data_from_microphone = Arc::new(Mutex::new(Vec::with_capacity(required_samples)));
+    // The callback pushes into a lock-free ring buffer that holds the whole recording, so it never waits on (or allocates
+    // for) the task that drains it.
+    let (mut producer, mut consumer) = RingBuffer::<f32>::new(required_samples);
 
-    // let data_from_microphone = Arc::new(Mutex::new(Vec::with_capacity(likely_sample_count as usize)));
     let last_error = Arc::new(Mutex::new(None));
 
     let stream = {
-        let result = data_from_microphone.clone();
         let last_error = last_error.clone();
 
-        device.build_input_stream::<f32, _, _>(
-            &config.into(),
-            move |data: &[_], _: &InputCallbackInfo| {
-                result.lock().unwrap().extend_from_slice(data);
-            },
-            move |err| {
-                last_error.lock().unwrap().replace(err);
-            },
-            None,
-        )?
+        device
+            .build_input_stream::<f32, _, _>(
+                &config.into(),
+                move |data: &[_], _: &InputCallbackInfo| {
+                    // Once the buffer is full, the recording is complete, so the rest of the data is dropped.
+                    if let Ok(chunk) = producer.write_chunk_uninit(data.len().min(producer.slots())) {
+                        chunk.fill_from_iter(data.iter().copied());
+                    }
+                },
+                move |err| {
+                    last_error.lock().unwrap().replace(err);
+                },
+                None,
+            )
+            .map_err(stream_error)?
     };
 
     // Begin recording.
 
-    stream.play()?;
-    futures_timer::Delay::new(Duration::from_secs_f32(length_in_seconds)).await;
+    stream.play().map_err(|e| KordError::StreamError(format!("Could not start the input stream: {e}.")))?;
+
+    wait_for_recording(Duration::from_secs_f32(length_in_seconds), cancellation, || consumer.slots() > 0).await?;
+
     drop(stream);
 
     // SAFETY: We are the only thread that can access the arc right now since the stream is dropped.
     if let Err(err) = Arc::try_unwrap(last_error).unwrap().into_inner() {
-        return Err(err.into());
+        return Err(stream_error(err));
     }
 
-    // SAFETY: We are the only thread that can access the arc right now since the stream is dropped.
-    let data_from_microphone = Arc::try_unwrap(data_from_microphone).unwrap().into_inner()?;
+    // Drain the recording (the stream is dropped, so nothing else is pushed).
+    let mut data_from_microphone = Vec::with_capacity(required_samples);
+
+    if let Ok(chunk) = consumer.read_chunk(consumer.slots()) {
+        let (first, second) = chunk.as_slices();
+
+        data_from_microphone.extend_from_slice(first);
+        data_from_microphone.extend_from_slice(second);
+
+        chunk.commit_all();
+    }
 
     Ok(data_from_microphone)
 }
 
+/// Waits out a recording of the given length, in short steps, so that cancellation (and a device that never delivers samples)
+/// is noticed, where `has_samples` reports whether the device has delivered any samples yet.
+///
+/// Returns [`KordError::Cancelled`] if the token (if any) is cancelled before any samples are delivered (or `Ok` if it is
+/// cancelled after, so that the samples so far are kept), and [`KordError::Timeout`] if no samples are delivered within
+/// [`DEVICE_TIMEOUT`] (or within the whole recording, if it is shorter).
+async fn wait_for_recording(length: Duration, cancellation: Option<&CancellationToken>, has_samples: impl Fn() -> bool) -> Res<()> {
+    let started = Instant::now();
+    let is_cancelled = || cancellation.map(|c| c.is_cancelled()).unwrap_or(false);
+
+    while let Some(remaining) = length.checked_sub(started.elapsed()).filter(|r| !r.is_zero()) {
+        if is_cancelled() {
+            break;
+        }
+
+        if !has_samples() && started.elapsed() >= DEVICE_TIMEOUT {
+            return Err(KordError::Timeout(DEVICE_TIMEOUT).into());
+        }
+
+        futures_timer::Delay::new(remaining.min(CANCELLATION_POLL_INTERVAL)).await;
+    }
+
+    if has_samples() {
+        Ok(())
+    } else if is_cancelled() {
+        Err(KordError::Cancelled.into())
+    } else {
+        Err(KordError::Timeout(length.min(DEVICE_TIMEOUT)).into())
+    }
+}
+
+/// Downmixes interleaved audio data to mono (i.e., averages each frame of `channels` samples).
+fn downmix_to_mono(data: &[f32], channels: usize) -> Vec<f32> {
+    data.chunks(channels.max(1)).map(|frame| frame.iter().sum::<f32>() / frame.len() as f32).collect()
+}
+
+/// Wraps an error from the audio stream (e.g., from building, or playing it).
+fn stream_error(error: impl std::fmt::Display) -> anyhow::Error {
+    KordError::StreamError(error.to_string()).into()
+}
+
 // Tests.
 
 #[cfg(test)]
 mod tests {
-    use std::{
-        f32::consts::PI,
-        sync::LazyLock
-    };
+    use std::sync::LazyLock;
     use rand::{
         rng,
         prelude::IndexedRandom
     };
     use crate::{
         core::base::HasName,
+        core::helpers::generate_tone,
         core::note::ALL_PITCH_NOTES,
         core::pitch::HasFrequency,
         core::{base::Parsable, chord::Chord, note::Note},
@@ -132,17 +490,93 @@ mod tests {
             .collect()
     });
 
-    fn generate_test_tone(duration: f32, frequencies: &[f32]) -> Vec<f32> {
-        static SAMPLE_RATE: u32 = 44100;
-        let sample_count = (duration * SAMPLE_RATE as f32) as usize;
-        let amplitude = 0.5 / frequencies.len() as f32;
+    #[test]
+    fn test_window_detection() {
+        use super::spawn_window_detection;
+        use crate::core::note::A;
+        use std::sync::mpsc::channel;
+
+        let (sender, chunks) = channel();
+        let (handle, receiver) = spawn_window_detection(chunks, 44_100, 1, 0.5, 0.25).unwrap();
+
+        // Push a second of an A, in chunks that do not line up with the windows.
+        let data = generate_tone(&[A.frequency()], 1.0, 44_100);
 
-        (0..sample_count)
-            .map(|i| {
-                let t = i as f32 / SAMPLE_RATE as f32;
-                frequencies.iter().fold(0.0, |acc, &freq| acc + (2.0 * PI * freq * t).sin() * amplitude)
+        for chunk in data.chunks(1_000) {
+            sender.send(chunk.to_vec()).unwrap();
+        }
+
+        drop(sender);
+        handle.join().unwrap();
+
+        let windows = receiver.iter().collect::<Vec<_>>();
+
+        assert_eq!(windows.len(), 3);
+        assert!(windows.iter().all(|notes| *notes == vec![A]));
+
+        // Dropping the receiver stops detection, without disturbing the sender.
+        let (sender, chunks) = channel();
+        let (handle, receiver) = spawn_window_detection(chunks, 44_100, 1, 0.5, 0.25).unwrap();
+
+        drop(receiver);
+        sender.send(data).unwrap();
+        handle.join().unwrap();
+
+        assert!(sender.send(vec![0.0]).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "analyze_stream")]
+    fn test_wait_for_recording_cancellation() {
+        use super::{wait_for_recording, CancellationToken};
+        use crate::core::base::KordError;
+        use futures::executor::block_on;
+        use std::time::{Duration, Instant};
+
+        // Cancelled before any samples arrive.
+        let token = CancellationToken::new();
+        token.cancel();
+
+        let result = block_on(wait_for_recording(Duration::from_secs(10), Some(&token), || false));
+        assert_eq!(result.unwrap_err().downcast_ref::<KordError>(), Some(&KordError::Cancelled));
+
+        // Cancelled during capture (from another thread), which keeps the samples so far.
+        let token = CancellationToken::new();
+        let canceller = {
+            let token = token.clone();
+
+            std::thread::spawn(move || {
+                std::thread::sleep(Duration::from_millis(50));
+                token.cancel();
             })
-            .collect()
+        };
+
+        let started = Instant::now();
+        assert!(block_on(wait_for_recording(Duration::from_secs(10), Some(&token), || true)).is_ok());
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        canceller.join().unwrap();
+
+        // Without cancellation, the whole length is waited out.
+        let started = Instant::now();
+        assert!(block_on(wait_for_recording(Duration::from_millis(300), None, || true)).is_ok());
+        assert!(started.elapsed() >= Duration::from_millis(300));
+    }
+
+    #[test]
+    #[cfg(feature = "analyze_stream")]
+    fn test_wait_for_recording_timeout() {
+        use super::{wait_for_recording, DEVICE_TIMEOUT};
+        use crate::core::base::KordError;
+        use futures::executor::block_on;
+        use std::time::Duration;
+
+        // A device that never delivers samples times out, even if the recording is shorter than the timeout.
+        let result = block_on(wait_for_recording(Duration::from_millis(300), None, || false));
+        assert_eq!(result.unwrap_err().downcast_ref::<KordError>(), Some(&KordError::Timeout(Duration::from_millis(300))));
+
+        let result = block_on(wait_for_recording(Duration::from_secs(10), None, || false));
+        assert_eq!(result.unwrap_err().downcast_ref::<KordError>(), Some(&KordError::Timeout(DEVICE_TIMEOUT)));
     }
 
     #[test]
@@ -162,7 +596,7 @@ mod tests {
 
         let selected_note = *VALID_NOTES_GUITAR.choose(&mut rng).unwrap();
 
-        let data = generate_test_tone(REALTIME_DURATION, &[selected_note.frequency()]);
+        let data = generate_tone(&[selected_note.frequency()], REALTIME_DURATION, 44_100);
 
         let notes = Note::try_from_audio(&data, REALTIME_DURATION).unwrap();
 
@@ -178,7 +612,7 @@ mod tests {
 
         let frequencies: Vec<f32> = selected_notes.iter().map(|n| n.frequency()).collect();
 
-        let data = generate_test_tone(REALTIME_DURATION, &frequencies);
+        let data = generate_tone(&frequencies, REALTIME_DURATION, 44_100);
 
         let detected_notes = Note::try_from_audio(&data, REALTIME_DURATION).unwrap();
 
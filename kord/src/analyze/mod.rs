@@ -8,3 +8,30 @@ pub mod mic;
 
 #[cfg(feature = "analyze_file")]
 pub mod file;
+
+#[cfg(feature = "analyze_base")]
+pub mod stream;
+
+#[cfg(feature = "analyze_base")]
+pub mod channels;
+
+#[cfg(feature = "analyze_base")]
+pub mod timeline;
+
+#[cfg(feature = "analyze_base")]
+pub mod onset;
+
+#[cfg(feature = "analyze_base")]
+pub mod annotate;
+
+#[cfg(feature = "analyze_base")]
+pub mod mono;
+
+#[cfg(feature = "analyze_base")]
+pub mod worker;
+
+#[cfg(feature = "analyze_base")]
+pub mod chroma;
+
+#[cfg(feature = "analyze_parallel")]
+pub mod parallel;
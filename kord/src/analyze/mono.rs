@@ -0,0 +1,352 @@
+//! Monophonic pitch tracking and tuning.
+//!
+//! Estimates the fundamental frequency of short frames with the YIN method (a normalized autocorrelation), so that
+//! continuous pitch changes (bends, slides, vibrato) can be followed rather than quantized to notes.  For band-limited
+//! audio (e.g., phone calls), the fundamental can instead be inferred from the harmonics with a harmonic product spectrum.
+
+use std::ops::Deref;
+
+use crate::core::{
+    note::{Note, ALL_PITCH_NOTES_WITH_FREQUENCY},
+    pitch::tuning,
+};
+
+use super::base::{binary_search_closest, get_frequency_space};
+
+// Statics.
+
+/// The frame size (in samples) used for pitch tracking.
+static PITCH_FRAME_SIZE: usize = 2_048;
+
+/// The hop size (in samples) used for pitch tracking.
+static PITCH_HOP_SIZE: usize = 512;
+
+/// The lowest frequency that pitch tracking will report.
+static PITCH_MIN_FREQUENCY: f32 = 50.0;
+
+/// The highest frequency that pitch tracking will report.
+static PITCH_MAX_FREQUENCY: f32 = 2_000.0;
+
+/// The YIN threshold below which a lag is accepted as the period.
+static YIN_THRESHOLD: f32 = 0.15;
+
+/// The RMS below which a frame is considered silent.
+static SILENCE_THRESHOLD: f32 = 1e-3;
+
+/// The number of (in-band) harmonics that are multiplied together by the harmonic product spectrum.
+static HPS_HARMONICS: usize = 5;
+
+/// The fraction of the strongest component's magnitude that a component must exceed to be considered in-band.
+static HPS_BAND_THRESHOLD: f32 = 0.1;
+
+/// The default tolerance (in cents) of a [`Tuner`].
+pub static DEFAULT_TUNER_TOLERANCE_IN_CENTS: f32 = 5.0;
+
+// Struct.
+
+/// A tuner, which compares detected pitches against the nearest in-tune notes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tuner {
+    tolerance_in_cents: f32,
+}
+
+/// A reading from a [`Tuner`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TunerReading {
+    /// The detected frequency.
+    pub frequency: f32,
+    /// The nearest in-tune note.
+    pub target: Note,
+    /// The frequency of the target note.
+    pub target_frequency: f32,
+    /// How far the detected frequency is from the target, in cents (positive is sharp, negative is flat).
+    pub cents: f32,
+    /// Whether or not the detected frequency is within the tuner's tolerance of the target.
+    pub in_tune: bool,
+}
+
+impl Tuner {
+    /// Creates a new tuner with the given tolerance (in cents).
+    pub fn new(tolerance_in_cents: f32) -> Self {
+        Self {
+            tolerance_in_cents: tolerance_in_cents.abs(),
+        }
+    }
+
+    /// Returns the tolerance (in cents) of the tuner.
+    pub fn tolerance_in_cents(&self) -> f32 {
+        self.tolerance_in_cents
+    }
+
+    /// Detects the pitch of a monophonic frame, and compares it against the nearest in-tune note.
+    ///
+    /// Returns `None` if the frame has no clear pitch.
+    pub fn read(&self, frame: &[f32], sample_rate: u32) -> Option<TunerReading> {
+        self.read_frequency(detect_pitch(frame, sample_rate)?)
+    }
+
+    /// Compares the given frequency against the nearest in-tune note.
+    ///
+    /// Returns `None` if the frequency is outside of the range of known notes.
+    pub fn read_frequency(&self, frequency: f32) -> Option<TunerReading> {
+        if !frequency.is_finite() || frequency <= 0.0 {
+            return None;
+        }
+
+        // Search in the log domain, so that "nearest" is in cents rather than in Hz (and in the standard tuning, like the table).
+        let ratio = tuning().ratio();
+        let (target, standard_frequency) = *binary_search_closest(ALL_PITCH_NOTES_WITH_FREQUENCY.deref(), (frequency / ratio).log2(), |t| t.1.log2())?;
+        let target_frequency = standard_frequency * ratio;
+        let cents = 1200.0 * (frequency / target_frequency).log2();
+
+        Some(TunerReading {
+            frequency,
+            target,
+            target_frequency,
+            cents,
+            in_tune: cents.abs() <= self.tolerance_in_cents,
+        })
+    }
+}
+
+impl Default for Tuner {
+    fn default() -> Self {
+        Self::new(DEFAULT_TUNER_TOLERANCE_IN_CENTS)
+    }
+}
+
+// Functions.
+
+/// Detects the pitch contour of monophonic audio data.
+///
+/// Returns a vector of (time in seconds, frequency) pairs, one per analysis frame; frames that are silent, or that have
+/// no clear pitch, are skipped.  The time is that of the center of the frame.
+pub fn detect_pitch_contour(data: &[f32], sample_rate: u32) -> Vec<(f32, f32)> {
+    if data.len() < PITCH_FRAME_SIZE || sample_rate == 0 {
+        return Vec::new();
+    }
+
+    (0..=(data.len() - PITCH_FRAME_SIZE))
+        .step_by(PITCH_HOP_SIZE)
+        .filter_map(|start| {
+            let frequency = detect_pitch(&data[start..start + PITCH_FRAME_SIZE], sample_rate)?;
+            let time = (start + PITCH_FRAME_SIZE / 2) as f32 / sample_rate as f32;
+
+            Some((time, frequency))
+        })
+        .collect()
+}
+
+/// Detects the fundamental frequency of a single monophonic frame, if it has a clear pitch.
+pub fn detect_pitch(frame: &[f32], sample_rate: u32) -> Option<f32> {
+    let rms = (frame.iter().map(|s| s * s).sum::<f32>() / frame.len().max(1) as f32).sqrt();
+
+    if rms < SILENCE_THRESHOLD || sample_rate == 0 {
+        return None;
+    }
+
+    let min_lag = (sample_rate as f32 / PITCH_MAX_FREQUENCY).floor().max(2.0) as usize;
+    let max_lag = ((sample_rate as f32 / PITCH_MIN_FREQUENCY).ceil() as usize).min(frame.len() / 2);
+
+    if min_lag + 1 >= max_lag {
+        return None;
+    }
+
+    // Compute the difference function, and its cumulative mean normalization.
+
+    let window = frame.len() - max_lag;
+    let difference = (0..=max_lag).map(|lag| (0..window).map(|k| (frame[k] - frame[k + lag]).powi(2)).sum::<f32>()).collect::<Vec<_>>();
+
+    let mut normalized = vec![1.0; difference.len()];
+    let mut running_sum = 0.0;
+
+    for lag in 1..difference.len() {
+        running_sum += difference[lag];
+        normalized[lag] = if running_sum > 0.0 { difference[lag] * lag as f32 / running_sum } else { 1.0 };
+    }
+
+    // Take the first dip below the threshold (walking down to its local minimum).
+
+    let mut lag = (min_lag..max_lag).find(|&lag| normalized[lag] < YIN_THRESHOLD)?;
+
+    while lag + 1 < max_lag && normalized[lag + 1] < normalized[lag] {
+        lag += 1;
+    }
+
+    // Refine the lag with parabolic interpolation.
+
+    let (a, b, c) = (normalized[lag - 1], normalized[lag], normalized[lag + 1]);
+    let denominator = a - 2.0 * b + c;
+    let offset = if denominator.abs() > f32::EPSILON { 0.5 * (a - c) / denominator } else { 0.0 };
+
+    Some(sample_rate as f32 / (lag as f32 + offset.clamp(-1.0, 1.0)))
+}
+
+/// Infers the fundamental frequency of monophonic audio data from its harmonic structure, using a harmonic product spectrum (HPS).
+///
+/// This is intended for band-limited audio (e.g., phone calls, or cheap mics, which cut off below ~300 Hz), where the
+/// fundamental of a low note may be missing entirely.  The DC offset is removed first, and only the harmonics that fall
+/// within the band (i.e., at, or above, the lowest strong component) are considered, so a missing fundamental is not
+/// held against the right candidate.
+pub fn detect_fundamental_hps(data: &[f32], sample_rate: u32) -> Option<f32> {
+    if data.is_empty() || sample_rate == 0 {
+        return None;
+    }
+
+    // Block DC, and get the magnitude spectrum.
+
+    let mean = data.iter().sum::<f32>() / data.len() as f32;
+    let centered = data.iter().map(|s| s - mean).collect::<Vec<_>>();
+
+    let length_in_seconds = data.len() as f32 / sample_rate as f32;
+    let spectrum = get_frequency_space(&centered, length_in_seconds);
+    let spectrum = &spectrum[..spectrum.len() / 2];
+    let bin_width = 1.0 / length_in_seconds;
+
+    let min_bin = (PITCH_MIN_FREQUENCY / bin_width).ceil() as usize;
+    let max_magnitude = spectrum.iter().skip(min_bin).map(|(_, m)| *m).fold(0.0, f32::max);
+
+    if max_magnitude <= 0.0 {
+        return None;
+    }
+
+    // Find the low edge of the band (the fundamental cannot be above the lowest strong component).
+
+    let band_low = spectrum.iter().skip(min_bin).find(|(_, m)| *m >= max_magnitude * HPS_BAND_THRESHOLD)?.0;
+
+    let magnitude_at = |frequency: f32| {
+        let k = (frequency / bin_width).round() as usize;
+
+        spectrum[k.saturating_sub(1)..(k + 2).min(spectrum.len())].iter().map(|(_, m)| m / max_magnitude).fold(0.0, f32::max)
+    };
+
+    // Score each candidate by the (log) product of its first in-band harmonics.
+
+    let nyquist = spectrum.len() as f32 * bin_width;
+    let mut best: Option<(f32, f32)> = None;
+    let mut candidate = PITCH_MIN_FREQUENCY;
+
+    while candidate <= band_low * 1.03 {
+        let harmonics = (1..)
+            .map(|h| candidate * h as f32)
+            .take_while(|f| *f < nyquist)
+            .filter(|f| *f >= band_low * 0.97)
+            .take(HPS_HARMONICS)
+            .collect::<Vec<_>>();
+
+        if harmonics.len() == HPS_HARMONICS {
+            let score = harmonics.iter().map(|f| (magnitude_at(*f) + 1e-6).ln()).sum::<f32>();
+
+            if best.map(|(_, best_score)| score > best_score).unwrap_or(true) {
+                best = Some((candidate, score));
+            }
+        }
+
+        candidate += bin_width / 2.0;
+    }
+
+    best.map(|(frequency, _)| frequency)
+}
+
+/// Infers the note of monophonic, band-limited audio data from its harmonic structure (see [`detect_fundamental_hps`]).
+pub fn detect_note_hps(data: &[f32], sample_rate: u32) -> Option<Note> {
+    Tuner::default().read_frequency(detect_fundamental_hps(data, sample_rate)?).map(|r| r.target)
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::note::{ATwo, A};
+    use pretty_assertions::assert_eq;
+    use std::f32::consts::PI;
+
+    #[test]
+    fn test_detect_pitch() {
+        let frame = (0..PITCH_FRAME_SIZE).map(|k| (2.0 * PI * 440.0 * k as f32 / 44_100.0).sin() * 0.5).collect::<Vec<_>>();
+
+        assert!((detect_pitch(&frame, 44_100).unwrap() - 440.0).abs() < 1.0);
+        assert_eq!(detect_pitch(&[0.0; PITCH_FRAME_SIZE], 44_100), None);
+    }
+
+    #[test]
+    fn test_tuner() {
+        // About 12 cents sharp of A4.
+        let frequency = 440.0 * 2f32.powf(12.0 / 1200.0);
+        let frame = (0..PITCH_FRAME_SIZE).map(|k| (2.0 * PI * frequency * k as f32 / 44_100.0).sin() * 0.5).collect::<Vec<_>>();
+
+        let reading = Tuner::new(5.0).read(&frame, 44_100).unwrap();
+
+        assert_eq!(reading.target, A);
+        assert_eq!(reading.target_frequency, 440.0);
+        assert!(reading.cents > 5.0 && reading.cents < 20.0, "Reading was {} cents.", reading.cents);
+        assert!(!reading.in_tune);
+
+        let reading = Tuner::default().read_frequency(438.0).unwrap();
+        assert_eq!(reading.target, A);
+        assert!(reading.cents < 0.0);
+        assert!(Tuner::new(10.0).read_frequency(438.0).unwrap().in_tune);
+    }
+
+    #[test]
+    fn test_detect_pitch_contour() {
+        // A linear glissando from A3 to A4 over one second.
+        let sample_rate = 44_100;
+        let (start, end) = (220.0f32, 440.0f32);
+
+        let mut phase = 0.0f32;
+        let data = (0..sample_rate)
+            .map(|k| {
+                let t = k as f32 / sample_rate as f32;
+                phase += 2.0 * PI * (start + (end - start) * t) / sample_rate as f32;
+
+                phase.sin() * 0.5
+            })
+            .collect::<Vec<_>>();
+
+        let contour = detect_pitch_contour(&data, sample_rate as u32);
+
+        assert!(contour.len() > 70);
+
+        // Every point follows the slide, and the contour rises smoothly (no jumps, no quantization to notes).
+        for (time, frequency) in &contour {
+            let expected = start + (end - start) * time;
+            assert!((frequency - expected).abs() / expected < 0.02, "Expected {expected} Hz at {time}s, got {frequency} Hz.");
+        }
+
+        for pair in contour.windows(2) {
+            let step = pair[1].1 - pair[0].1;
+            assert!(step > 0.0 && step < 5.0, "Contour stepped by {step} Hz.");
+        }
+    }
+
+    #[test]
+    fn test_detect_note_hps() {
+        // A bright A2 (110 Hz), high-passed at 300 Hz like a phone line, so that the fundamental (and second harmonic) are all but gone.
+        let sample_rate = 44_100;
+        let mut data = (0..sample_rate)
+            .map(|k| {
+                let t = k as f32 / sample_rate as f32;
+
+                (1..=12).map(|h| (2.0 * PI * 110.0 * h as f32 * t).sin() / h as f32).sum::<f32>() * 0.2 + 0.1
+            })
+            .collect::<Vec<_>>();
+
+        let alpha = 1.0 / (1.0 + 2.0 * PI * 300.0 / sample_rate as f32);
+        for _ in 0..4 {
+            let mut previous_input = data[0];
+            let mut previous_output = 0.0;
+
+            for sample in data.iter_mut() {
+                let output = alpha * (previous_output + *sample - previous_input);
+                previous_input = *sample;
+                previous_output = output;
+                *sample = output;
+            }
+        }
+
+        assert_eq!(detect_note_hps(&data, sample_rate as u32), Some(ATwo));
+        assert_eq!(detect_note_hps(&[0.0; 4_096], 44_100), None);
+    }
+}
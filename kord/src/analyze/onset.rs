@@ -0,0 +1,291 @@
+//! Onset detection, beat tracking, and latency compensation.
+//!
+//! Onsets are found from rises in the short-time energy of the signal, and beats are laid out on a grid whose period is
+//! the strongest periodicity of the spectral flux of the signal.
+
+use std::time::Duration;
+
+use rustfft::{
+    num_complex::{Complex, ComplexFloat},
+    FftPlanner,
+};
+
+// Statics.
+
+/// The frame size (in samples) used for onset detection.
+static ONSET_FRAME_SIZE: usize = 256;
+
+/// The minimum time (in seconds) between two onsets.
+static ONSET_MIN_GAP_IN_SECONDS: f32 = 0.05;
+
+/// The fraction of the largest energy rise (or spectral flux) that a rise must exceed to be considered an onset.
+static ONSET_THRESHOLD: f32 = 0.1;
+
+/// The frame size (in samples) used for beat detection.
+static BEAT_FRAME_SIZE: usize = 1024;
+
+/// The hop size (in samples) used for beat detection.
+static BEAT_HOP_SIZE: usize = 512;
+
+/// The shortest beat period (in seconds) that is detected (i.e., 240 bpm).
+pub static BEAT_MIN_PERIOD_IN_SECONDS: f32 = 0.25;
+
+/// The longest beat period (in seconds) that is detected (i.e., 40 bpm).
+pub static BEAT_MAX_PERIOD_IN_SECONDS: f32 = 1.5;
+
+// Functions.
+
+/// Detects the onsets (in seconds from the start of the data) in the audio data.
+pub fn detect_onsets(data: &[f32], sample_rate: u32) -> Vec<f32> {
+    if data.len() < ONSET_FRAME_SIZE || sample_rate == 0 {
+        return Vec::new();
+    }
+
+    // Compute the energy of each frame, and how much it rises from the previous frame.
+
+    let energies = data.chunks_exact(ONSET_FRAME_SIZE).map(|frame| frame.iter().map(|s| s * s).sum::<f32>()).collect::<Vec<_>>();
+
+    let rises = std::iter::once(energies[0]).chain(energies.windows(2).map(|w| (w[1] - w[0]).max(0.0))).collect::<Vec<_>>();
+
+    let max_rise = rises.iter().copied().fold(0.0, f32::max);
+
+    if max_rise <= 0.0 {
+        return Vec::new();
+    }
+
+    // Pick the local maxima of the rises that are above the threshold, and far enough apart.
+
+    let min_gap = (ONSET_MIN_GAP_IN_SECONDS * sample_rate as f32) as usize;
+    let mut onsets: Vec<usize> = Vec::new();
+
+    for k in 0..rises.len() {
+        let rise = rises[k];
+        let is_peak = rise > max_rise * ONSET_THRESHOLD && (k == 0 || rise >= rises[k - 1]) && (k + 1 == rises.len() || rise > rises[k + 1]);
+
+        if !is_peak {
+            continue;
+        }
+
+        // The sound may have started in the previous frame, so find where the signal actually starts across both.
+        let onset = find_signal_start(data, k.saturating_sub(1) * ONSET_FRAME_SIZE, (k + 1) * ONSET_FRAME_SIZE);
+
+        match onsets.last() {
+            Some(last) if onset.saturating_sub(*last) < min_gap => {}
+            _ => onsets.push(onset),
+        }
+    }
+
+    onsets.into_iter().map(|k| k as f32 / sample_rate as f32).collect()
+}
+
+/// Detects the beats (in seconds from the start of the data) in the audio data.
+///
+/// The beats are found from the spectral flux of the signal (which, unlike its energy, is not fooled by tones beating
+/// against each other).  The beat period is the strongest periodicity of the flux between [`BEAT_MIN_PERIOD_IN_SECONDS`] and
+/// [`BEAT_MAX_PERIOD_IN_SECONDS`], and the grid starts at the first onset.  Each beat snaps to the strongest onset within a
+/// quarter of a period of where it is expected (so the grid follows small tempo drifts, and skips over missing beats), and
+/// the grid continues through the end of the data.
+pub fn detect_beats(data: &[f32], sample_rate: u32) -> Vec<f32> {
+    if data.len() < BEAT_FRAME_SIZE || sample_rate == 0 {
+        return Vec::new();
+    }
+
+    let flux = get_spectral_flux(data);
+    let max_flux = flux.iter().copied().fold(0.0, f32::max);
+
+    if max_flux <= 0.0 {
+        return Vec::new();
+    }
+
+    let frames_per_second = sample_rate as f32 / BEAT_HOP_SIZE as f32;
+    let is_onset = |k: usize| flux[k] > max_flux * ONSET_THRESHOLD;
+
+    // The time of a frame is the start of its newest hop, refined to where the signal actually starts.
+    let time_of = |k: usize| {
+        let search_start = (k * BEAT_HOP_SIZE + BEAT_FRAME_SIZE).saturating_sub(2 * BEAT_HOP_SIZE);
+        let search_end = (k * BEAT_HOP_SIZE + BEAT_FRAME_SIZE).min(data.len());
+
+        find_signal_start(data, search_start, search_end) as f32 / sample_rate as f32
+    };
+
+    // Find the beat period, from the autocorrelation of the flux.
+
+    let min_lag = (BEAT_MIN_PERIOD_IN_SECONDS * frames_per_second) as usize;
+    let max_lag = ((BEAT_MAX_PERIOD_IN_SECONDS * frames_per_second) as usize).min(flux.len().saturating_sub(1));
+
+    let Some(first) = (0..flux.len()).find(|k| is_onset(*k)) else {
+        return Vec::new();
+    };
+
+    if min_lag >= max_lag {
+        return vec![time_of(first)];
+    }
+
+    let correlations = (min_lag..=max_lag)
+        .map(|lag| (lag, flux.iter().zip(&flux[lag..]).map(|(a, b)| a * b).sum::<f32>() / (flux.len() - lag) as f32))
+        .collect::<Vec<_>>();
+    let max_correlation = correlations.iter().map(|(_, c)| *c).fold(0.0, f32::max);
+
+    // Multiples of the period correlate about as well as the period itself, so prefer the shortest strong lag.
+    let Some(period) = correlations.iter().find(|(_, c)| *c >= max_correlation * 0.8).map(|(lag, _)| *lag) else {
+        return vec![time_of(first)];
+    };
+
+    // Lay out the grid.
+
+    let mut beats = vec![time_of(first)];
+    let mut frame = first;
+
+    loop {
+        let predicted = frame + period;
+
+        if predicted >= flux.len() {
+            break;
+        }
+
+        let window = predicted.saturating_sub(period / 4)..(predicted + period / 4 + 1).min(flux.len());
+        let strongest = window.max_by(|a, b| flux[*a].partial_cmp(&flux[*b]).unwrap()).filter(|k| is_onset(*k));
+
+        // A missing beat falls exactly one period after the previous one.
+        let beat = match strongest {
+            Some(k) => time_of(k),
+            None => beats[beats.len() - 1] + period as f32 / frames_per_second,
+        };
+
+        beats.push(beat);
+        frame = strongest.unwrap_or(predicted);
+    }
+
+    beats
+}
+
+/// Estimates the latency of a recording, given the time (in seconds) at which a click was played into it.
+///
+/// The latency is the distance between the click time and the first onset detected at, or after, it.
+pub fn estimate_latency(recorded: &[f32], sample_rate: u32, click_time_in_seconds: f32) -> Option<Duration> {
+    detect_onsets(recorded, sample_rate)
+        .into_iter()
+        .find(|onset| *onset >= click_time_in_seconds)
+        .map(|onset| Duration::from_secs_f32(onset - click_time_in_seconds))
+}
+
+/// Offsets timestamps (in seconds) by a known latency, so that they align with the time that the sound was actually made.
+///
+/// Timestamps that would become negative are clamped to zero.
+pub fn compensate_latency(timestamps: &[f32], latency: Duration) -> Vec<f32> {
+    let latency = latency.as_secs_f32();
+
+    timestamps.iter().map(|t| (t - latency).max(0.0)).collect()
+}
+
+/// Generates a short click (in a buffer of `length_in_seconds`) at the given time, for latency calibration.
+pub fn generate_click(sample_rate: u32, length_in_seconds: f32, click_time_in_seconds: f32) -> Vec<f32> {
+    let num_samples = (sample_rate as f32 * length_in_seconds) as usize;
+    let click_start = (sample_rate as f32 * click_time_in_seconds) as usize;
+    let click_length = (sample_rate as f32 * 0.01) as usize;
+
+    (0..num_samples)
+        .map(|k| {
+            if (click_start..click_start + click_length).contains(&k) {
+                let t = (k - click_start) as f32 / sample_rate as f32;
+
+                (2.0 * std::f32::consts::PI * 1_000.0 * t).sin() * 0.8
+            } else {
+                0.0
+            }
+        })
+        .collect()
+}
+
+// Helpers.
+
+/// Gets the spectral flux of each (overlapping) frame of the data: the total rise in magnitude of each frequency bin from
+/// the previous frame.
+fn get_spectral_flux(data: &[f32]) -> Vec<f32> {
+    let mut planner = FftPlanner::new();
+    let fft = planner.plan_fft_forward(BEAT_FRAME_SIZE);
+
+    let mut previous = vec![0.0; BEAT_FRAME_SIZE / 2];
+
+    (0..=(data.len() - BEAT_FRAME_SIZE) / BEAT_HOP_SIZE)
+        .map(|k| {
+            let mut buffer = data[k * BEAT_HOP_SIZE..k * BEAT_HOP_SIZE + BEAT_FRAME_SIZE].iter().map(|s| Complex::new(*s, 0.0)).collect::<Vec<_>>();
+            fft.process(&mut buffer);
+
+            let magnitudes = buffer.into_iter().take(BEAT_FRAME_SIZE / 2).map(|c| c.abs()).collect::<Vec<_>>();
+            let flux = magnitudes.iter().zip(&previous).map(|(m, p)| (m - p).max(0.0)).sum::<f32>();
+
+            previous = magnitudes;
+
+            flux
+        })
+        .collect()
+}
+
+/// Finds where the signal actually starts within the given range of the data (i.e., the first sample that reaches half of
+/// the peak amplitude of the range).
+fn find_signal_start(data: &[f32], start: usize, end: usize) -> usize {
+    let search = &data[start..end];
+    let peak = search.iter().fold(0.0f32, |acc, s| acc.max(s.abs()));
+
+    start + search.iter().position(|s| s.abs() >= peak * 0.5).unwrap_or(0)
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_detect_onsets() {
+        let mut data = generate_click(44100, 2.0, 0.5);
+        let second = generate_click(44100, 2.0, 1.5);
+        data.iter_mut().zip(second).for_each(|(a, b)| *a += b);
+
+        let onsets = detect_onsets(&data, 44100);
+
+        assert_eq!(onsets.len(), 2);
+        assert!((onsets[0] - 0.5).abs() < 0.005);
+        assert!((onsets[1] - 1.5).abs() < 0.005);
+
+        assert_eq!(detect_onsets(&[0.0; 4096], 44100), vec![]);
+    }
+
+    #[test]
+    fn test_detect_beats() {
+        // Clicks every half second (120 bpm), with one missing, and one a little late.
+        let mut data = vec![0.0; 44100 * 3];
+
+        for click in [0.25, 0.75, 1.25, 2.26, 2.75] {
+            data.iter_mut().zip(generate_click(44100, 3.0, click)).for_each(|(a, b)| *a += b);
+        }
+
+        let beats = detect_beats(&data, 44100);
+        let expected = [0.25, 0.75, 1.25, 1.75, 2.26, 2.75];
+
+        assert_eq!(beats.len(), expected.len());
+
+        for (beat, expected) in beats.iter().zip(expected) {
+            assert!((beat - expected).abs() < 0.005, "{beat} should be close to {expected}");
+        }
+
+        assert_eq!(detect_beats(&[0.0; 4096], 44100), vec![]);
+    }
+
+    #[test]
+    fn test_latency_compensation() {
+        // Simulate a recording of a click played at 0.5s, which arrives 120ms late.
+        let recorded = generate_click(44100, 1.5, 0.62);
+
+        let latency = estimate_latency(&recorded, 44100, 0.5).unwrap();
+        assert!((latency.as_secs_f32() - 0.12).abs() < 0.005, "Estimated latency was {latency:?}.");
+
+        let aligned = compensate_latency(&detect_onsets(&recorded, 44100), latency);
+        assert_eq!(aligned.len(), 1);
+        assert!((aligned[0] - 0.5).abs() < 0.001);
+
+        assert_eq!(compensate_latency(&[0.05, 1.0], Duration::from_millis(100)), vec![0.0, 0.9]);
+    }
+}
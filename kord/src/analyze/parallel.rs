@@ -0,0 +1,65 @@
+//! Parallel analysis of many clips.
+//!
+//! Clips are analyzed on a [`rayon`] thread pool: either the global pool, or one supplied by the caller, so that servers
+//! processing many files at once can bound how much CPU the analysis uses.
+
+use rayon::{prelude::*, ThreadPool, ThreadPoolBuilder};
+
+use crate::core::{
+    base::Res,
+    note::Note,
+    pitch::{tuning, with_tuning},
+};
+
+use super::base::get_notes_from_audio_data;
+
+// Functions.
+
+/// Gets the notes of each clip (sampled at `sample_rate`), analyzing the clips in parallel on the global [`rayon`] pool.
+///
+/// The results are in the same order as the clips.
+pub fn get_notes_from_audio_clips(clips: &[Vec<f32>], sample_rate: u32) -> Vec<Res<Vec<Note>>> {
+    // The tuning is per thread, so carry the caller's over to the pool's threads.
+    let tuning = tuning();
+
+    clips
+        .par_iter()
+        .map(|clip| with_tuning(tuning, || get_notes_from_audio_data(clip, clip.len() as f32 / sample_rate as f32)))
+        .collect()
+}
+
+/// Gets the notes of each clip (sampled at `sample_rate`), analyzing the clips in parallel on the given pool.
+///
+/// The results are in the same order as the clips.
+pub fn get_notes_from_audio_clips_in_pool(pool: &ThreadPool, clips: &[Vec<f32>], sample_rate: u32) -> Vec<Res<Vec<Note>>> {
+    pool.install(|| get_notes_from_audio_clips(clips, sample_rate))
+}
+
+/// Builds a thread pool with the given number of threads, for use with [`get_notes_from_audio_clips_in_pool`].
+pub fn build_analysis_pool(num_threads: usize) -> Res<ThreadPool> {
+    Ok(ThreadPoolBuilder::new().num_threads(num_threads).build()?)
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{
+        helpers::generate_tone,
+        note::{A, C, E},
+        pitch::HasFrequency,
+    };
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_single_thread_pool() {
+        let pool = build_analysis_pool(1).unwrap();
+        assert_eq!(pool.current_num_threads(), 1);
+
+        let clips = [A, C, E].iter().map(|n| generate_tone(&[n.frequency()], 0.5, 44_100)).collect::<Vec<_>>();
+        let results = get_notes_from_audio_clips_in_pool(&pool, &clips, 44100).into_iter().map(|r| r.unwrap()).collect::<Vec<_>>();
+
+        assert_eq!(results, vec![vec![A], vec![C], vec![E]]);
+    }
+}
@@ -0,0 +1,251 @@
+//! Streaming analysis of continuous audio.
+//!
+//! Splits a continuous source of samples into overlapping windows, and runs note detection on each window.
+
+#[cfg(feature = "analyze_stream")]
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+#[cfg(feature = "analyze_stream")]
+use futures::{Stream, StreamExt};
+
+use crate::core::{
+    base::{KordError, Res},
+    note::Note,
+};
+
+use super::{base::analyze_samples, channels::ChannelLayout};
+
+// Struct.
+
+/// A sliding window over a continuous source of samples.
+///
+/// Samples are pushed in arbitrarily sized chunks, and windows of `window_size` samples are yielded every `hop_size` samples.
+#[derive(Debug, Clone)]
+pub struct SlidingWindow {
+    window_size: usize,
+    hop_size: usize,
+    buffer: Vec<f32>,
+}
+
+impl SlidingWindow {
+    /// Creates a new sliding window with the given window and hop sizes (in samples).
+    pub fn new(window_size: usize, hop_size: usize) -> Self {
+        Self {
+            window_size: window_size.max(1),
+            hop_size: hop_size.max(1),
+            buffer: Vec::with_capacity(window_size),
+        }
+    }
+
+    /// Pushes more samples into the window.
+    pub fn push(&mut self, samples: &[f32]) {
+        self.buffer.extend_from_slice(samples);
+    }
+
+    /// Returns the next full window, if enough samples have been pushed, and advances the window by the hop size.
+    pub fn next_window(&mut self) -> Option<Vec<f32>> {
+        if self.buffer.len() < self.window_size {
+            return None;
+        }
+
+        let window = self.buffer[..self.window_size].to_vec();
+        self.buffer.drain(..self.hop_size.min(self.buffer.len()));
+
+        Some(window)
+    }
+}
+
+/// A [`Stream`](futures::Stream) of detected notes, driven by a source of sample chunks.
+///
+/// Each item is the result of note detection over one window of the source (downmixed to mono).  Over a blocking source (e.g.,
+/// a channel's receiver), it is an [`Iterator`], instead.
+pub struct NoteStream<S> {
+    source: S,
+    window: SlidingWindow,
+    layout: ChannelLayout,
+    sample_rate: u32,
+}
+
+impl<S> NoteStream<S> {
+    /// Detects the notes in the next full window, if enough samples have been pushed.
+    fn next_notes(&mut self) -> Option<Vec<Note>> {
+        let window = self.window.next_window()?;
+
+        // A window that cannot be analyzed is treated as silence, so the stream keeps going.
+        let notes = self
+            .layout
+            .downmix_to_mono(&window)
+            .and_then(|mono| analyze_samples(&mono, self.sample_rate, mono.len() as f32 / self.sample_rate as f32))
+            .unwrap_or_default();
+
+        Some(notes)
+    }
+}
+
+#[cfg(feature = "analyze_stream")]
+impl<S> Stream for NoteStream<S>
+where
+    S: Stream<Item = Vec<f32>> + Unpin,
+{
+    type Item = Vec<Note>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            if let Some(notes) = self.next_notes() {
+                return Poll::Ready(Some(notes));
+            }
+
+            match self.source.poll_next_unpin(cx) {
+                Poll::Ready(Some(chunk)) => self.window.push(&chunk),
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+impl<S> Iterator for NoteStream<S>
+where
+    S: Iterator<Item = Vec<f32>>,
+{
+    type Item = Vec<Note>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(notes) = self.next_notes() {
+                return Some(notes);
+            }
+
+            let chunk = self.source.next()?;
+            self.window.push(&chunk);
+        }
+    }
+}
+
+// Helpers.
+
+/// Creates a [`NoteStream`] over the given source of sample chunks.
+///
+/// The source holds `channels` interleaved channels, sampled at `sample_rate` (in Hz), and each window is downmixed to mono
+/// before detection.  The window and hop lengths are in seconds.
+pub fn note_stream_from_source<S>(source: S, sample_rate: u32, channels: usize, window_in_seconds: f32, hop_in_seconds: f32) -> Res<NoteStream<S>> {
+    if window_in_seconds < 0.2 {
+        return Err(KordError::InvalidArgument("Window length in seconds must be greater than 0.2.".to_string()).into());
+    }
+
+    if hop_in_seconds <= 0.0 {
+        return Err(KordError::InvalidArgument("Hop length in seconds must be greater than 0.".to_string()).into());
+    }
+
+    if sample_rate == 0 {
+        return Err(KordError::InvalidArgument("Sample rate must be greater than 0.".to_string()).into());
+    }
+
+    if channels == 0 {
+        return Err(KordError::InvalidArgument("Number of channels must be greater than 0.".to_string()).into());
+    }
+
+    // The windows span whole frames, so that they stay aligned with the interleaved channels.
+    let window_size = (sample_rate as f32 * window_in_seconds) as usize * channels;
+    let hop_size = ((sample_rate as f32 * hop_in_seconds) as usize).max(1) * channels;
+
+    Ok(NoteStream {
+        source,
+        window: SlidingWindow::new(window_size, hop_size),
+        layout: ChannelLayout::interleaved(channels),
+        sample_rate,
+    })
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_sliding_window() {
+        let mut window = SlidingWindow::new(4, 2);
+
+        window.push(&[0.0, 1.0, 2.0]);
+        assert_eq!(window.next_window(), None);
+
+        window.push(&[3.0, 4.0, 5.0]);
+        assert_eq!(window.next_window(), Some(vec![0.0, 1.0, 2.0, 3.0]));
+        assert_eq!(window.next_window(), Some(vec![2.0, 3.0, 4.0, 5.0]));
+        assert_eq!(window.next_window(), None);
+    }
+
+    #[test]
+    #[cfg(feature = "analyze_stream")]
+    fn test_note_stream() {
+        use crate::core::{helpers::generate_tone, note::A, pitch::HasFrequency};
+        use futures::{executor::block_on, stream};
+
+        let data = generate_tone(&[A.frequency()], 1.0, 44_100);
+        let chunks = data.chunks(1024).map(|c| c.to_vec()).collect::<Vec<_>>();
+
+        let mut note_stream = note_stream_from_source(stream::iter(chunks), 44100, 1, 0.5, 0.25).unwrap();
+
+        let items = block_on(async {
+            let mut items = Vec::new();
+
+            while let Some(notes) = note_stream.next().await {
+                items.push(notes);
+            }
+
+            items
+        });
+
+        assert_eq!(items.len(), 3);
+        assert!(items.iter().all(|notes| notes == &vec![A]));
+    }
+
+    #[test]
+    fn test_note_stream_iterator() {
+        use crate::core::{helpers::generate_tone, note::A, pitch::HasFrequency};
+
+        let data = generate_tone(&[A.frequency()], 1.0, 44_100);
+        let chunks = data.chunks(1024).map(|c| c.to_vec()).collect::<Vec<_>>();
+
+        let items = note_stream_from_source(chunks.into_iter(), 44100, 1, 0.5, 0.25).unwrap().collect::<Vec<_>>();
+
+        assert_eq!(items.len(), 3);
+        assert!(items.iter().all(|notes| notes == &vec![A]));
+    }
+
+    #[test]
+    #[cfg(feature = "analyze_stream")]
+    fn test_note_stream_stereo() {
+        use crate::core::{
+            helpers::generate_tone,
+            note::{A, E},
+            pitch::HasFrequency,
+        };
+        use futures::{executor::block_on, stream};
+
+        // An A on the left channel, and an E on the right channel, sampled at 48 kHz.
+        let left = generate_tone(&[A.frequency()], 1.0, 48_000);
+        let right = generate_tone(&[E.frequency()], 1.0, 48_000);
+        let data = left.into_iter().zip(right).flat_map(|(l, r)| [l, r]).collect::<Vec<_>>();
+
+        // The chunks (like those of an input device) hold whole frames.
+        let chunks = data.chunks(2 * 1000).map(|c| c.to_vec()).collect::<Vec<_>>();
+
+        let items = block_on(note_stream_from_source(stream::iter(chunks), 48_000, 2, 0.5, 0.25).unwrap().collect::<Vec<_>>());
+
+        assert_eq!(items.len(), 3);
+        assert!(items.iter().all(|notes| notes == &vec![E, A]), "{items:?}");
+    }
+
+    #[test]
+    #[cfg(feature = "analyze_stream")]
+    fn test_note_stream_short_window() {
+        assert!(note_stream_from_source(futures::stream::iter(Vec::<Vec<f32>>::new()), 44100, 1, 0.1, 0.05).is_err());
+        assert!(note_stream_from_source(futures::stream::iter(Vec::<Vec<f32>>::new()), 44100, 0, 0.5, 0.25).is_err());
+    }
+}
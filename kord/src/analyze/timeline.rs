@@ -0,0 +1,272 @@
+//! Windowed analysis of audio clips.
+//!
+//! Runs detection over consecutive (possibly overlapping) windows of a clip, and combines the results.
+
+use crate::core::{
+    chord::{Chord, HasRoot},
+    note::Note,
+    pitch::HasPitch,
+};
+
+use super::{
+    base::{get_chord_from_audio_data_with_config, get_frequency_space, get_notes_from_audio_data_with_config, is_noise_like, sanitize_audio_data, AnalysisConfig, AnalysisPreset, Backend},
+    chroma::{get_chord_from_chroma_with_prior, get_chroma},
+    onset::detect_beats,
+};
+
+// Statics.
+
+/// The window length (in seconds) used for windowed chord detection.
+pub static DEFAULT_WINDOW_IN_SECONDS: f32 = 0.5;
+
+/// The hop length (in seconds) used for windowed chord detection.
+pub static DEFAULT_HOP_IN_SECONDS: f32 = 0.25;
+
+// Functions.
+
+/// Gets the most likely chord of each window of the clip.
+///
+/// Returns a vector of (start time in seconds, chord) pairs, skipping windows where no chord could be detected.
+pub fn get_chords_from_audio_data_windowed(data: &[f32], sample_rate: u32, window_in_seconds: f32, hop_in_seconds: f32) -> Vec<(f32, Chord)> {
+    get_chords_from_audio_data_windowed_with_config(data, sample_rate, window_in_seconds, hop_in_seconds, &AnalysisConfig::default())
+}
+
+/// Gets the most likely chord of each window of the clip, using the given [`AnalysisConfig`].
+///
+/// Returns a vector of (start time in seconds, chord) pairs, skipping windows where no chord could be detected.  If the config
+/// has a [`AnalysisConfig::temporal_prior`], each window is biased toward chords that are related to the chord detected in
+/// the previous window: diatonically related chords, for the [`Backend::ChromaTemplate`] backend, and (since the readings of a
+/// window's notes all share its pitch classes) readings on the previous chord's root, for the [`Backend::Peaks`] backend.
+pub fn get_chords_from_audio_data_windowed_with_config(data: &[f32], sample_rate: u32, window_in_seconds: f32, hop_in_seconds: f32, config: &AnalysisConfig) -> Vec<(f32, Chord)> {
+    let window_size = (sample_rate as f32 * window_in_seconds) as usize;
+    let hop_size = ((sample_rate as f32 * hop_in_seconds) as usize).max(1);
+
+    if window_size == 0 || data.len() < window_size {
+        return Vec::new();
+    }
+
+    let mut result: Vec<(f32, Chord)> = Vec::new();
+
+    for start in (0..=(data.len() - window_size)).step_by(hop_size) {
+        let window = &data[start..start + window_size];
+
+        let chord = match config.backend {
+            Backend::ChromaTemplate if config.temporal_prior > 0.0 => {
+                let window = sanitize_audio_data(window);
+                let previous = result.last().map(|(_, c)| c);
+
+                if is_noise_like(&get_frequency_space(&window, window_in_seconds), config) {
+                    None
+                } else {
+                    get_chord_from_chroma_with_prior(&get_chroma(&window, window_in_seconds), previous, config.temporal_prior)
+                }
+            }
+            Backend::Peaks if config.temporal_prior > 0.0 => {
+                let previous = result.last().map(|(_, c)| c);
+
+                get_notes_from_audio_data_with_config(window, window_in_seconds, config)
+                    .ok()
+                    .and_then(|notes| get_chord_from_notes_with_prior(&notes, previous, config.temporal_prior))
+            }
+            _ => get_chord_from_audio_data_with_config(window, window_in_seconds, config).ok().flatten(),
+        };
+
+        if let Some(chord) = chord {
+            result.push((start as f32 / sample_rate as f32, chord));
+        }
+    }
+
+    result
+}
+
+/// Gets the most likely chord of each window of the clip, using the window length, hop length, and [`AnalysisConfig`] of the
+/// given [`AnalysisPreset`].
+pub fn get_chords_from_audio_data_with_preset(data: &[f32], sample_rate: u32, preset: AnalysisPreset) -> Vec<(f32, Chord)> {
+    get_chords_from_audio_data_windowed_with_config(data, sample_rate, preset.window_in_seconds(), preset.hop_in_seconds(), &preset.config())
+}
+
+/// Transcribes the clip into a beat grid (see [`detect_beats`]), and a chord timeline that is aligned to it.
+///
+/// Returns the beats (in seconds), and the (start time in seconds, chord) pairs, where each chord starts on the beat at which
+/// it changes; the chord of each beat is detected over the span to the next beat (or the end of the clip).  If no beats can be
+/// detected, the chords are detected over the default windows instead (see [`get_chords_from_audio_data_windowed`]).
+pub fn transcribe_with_beats(data: &[f32], sample_rate: u32) -> (Vec<f32>, Vec<(f32, Chord)>) {
+    let beats = detect_beats(data, sample_rate);
+
+    if beats.is_empty() {
+        return (beats, get_chords_from_audio_data_windowed(data, sample_rate, DEFAULT_WINDOW_IN_SECONDS, DEFAULT_HOP_IN_SECONDS));
+    }
+
+    let config = AnalysisConfig::default();
+    let mut chords: Vec<(f32, Chord)> = Vec::new();
+
+    for (k, beat) in beats.iter().enumerate() {
+        let start = (beat * sample_rate as f32) as usize;
+        let end = beats.get(k + 1).map(|b| (b * sample_rate as f32) as usize).unwrap_or(data.len()).min(data.len());
+
+        if start >= end {
+            continue;
+        }
+
+        let span = &data[start..end];
+        let Ok(Some(chord)) = get_chord_from_audio_data_with_config(span, span.len() as f32 / sample_rate as f32, &config) else {
+            continue;
+        };
+
+        if chords.last().map(|(_, c)| *c != chord).unwrap_or(true) {
+            chords.push((*beat, chord));
+        }
+    }
+
+    (beats, chords)
+}
+
+/// Gets the chord that is detected in the most windows across the whole clip (i.e., a majority vote).
+///
+/// Ties are broken in favor of the chord that was detected first.
+pub fn dominant_chord(data: &[f32], sample_rate: u32) -> Option<Chord> {
+    let chords = get_chords_from_audio_data_windowed(data, sample_rate, DEFAULT_WINDOW_IN_SECONDS, DEFAULT_HOP_IN_SECONDS);
+
+    let mut votes: Vec<(Chord, usize)> = Vec::new();
+
+    for (_, chord) in chords {
+        match votes.iter_mut().find(|(c, _)| *c == chord) {
+            Some((_, count)) => *count += 1,
+            None => votes.push((chord, 1)),
+        }
+    }
+
+    // `max_by_key` returns the last maximum, so reverse to prefer the earliest chord.
+    votes.into_iter().rev().max_by_key(|(_, count)| *count).map(|(chord, _)| chord)
+}
+
+// Helpers.
+
+/// Gets the best reading of the notes (see [`Chord::try_from_notes_ranked`]), with `prior_strength` added to the score of the
+/// readings that share the root of `previous`.  Returns `None` if the notes are not a chord.
+fn get_chord_from_notes_with_prior(notes: &[Note], previous: Option<&Chord>, prior_strength: f32) -> Option<Chord> {
+    let mut best: Option<(Chord, f32)> = None;
+
+    for (chord, mut score) in Chord::try_from_notes_ranked(notes) {
+        if previous.map(|p| p.root().pitch() == chord.root().pitch()).unwrap_or(false) {
+            score += prior_strength;
+        }
+
+        if best.as_ref().map(|(_, best_score)| score > *best_score).unwrap_or(true) {
+            best = Some((chord, score));
+        }
+    }
+
+    best.map(|(chord, _)| chord)
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{
+        base::{HasName, Parsable},
+        chord::HasChord,
+        helpers::generate_tone,
+        note::{CFive, EFive, GSharp, A, B, C, D, E, G},
+        pitch::HasFrequency,
+    };
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_dominant_chord() {
+        let chord_tone = |chord: &str, duration: f32| {
+            let frequencies = Chord::parse(chord).unwrap().chord().iter().map(|n| n.frequency()).collect::<Vec<_>>();
+
+            generate_tone(&frequencies, duration, 44_100)
+        };
+
+        // Mostly Cmaj7, with a short glitch in the middle.
+        let mut data = chord_tone("Cmaj7", 1.5);
+        data.extend(chord_tone("F#m", 0.5));
+        data.extend(chord_tone("Cmaj7", 1.5));
+
+        assert_eq!(dominant_chord(&data, 44100), Some(Chord::parse("Cmaj7").unwrap()));
+    }
+
+    #[test]
+    fn test_temporal_prior() {
+        // G major, followed by a borderline chord: E and B, with a G♯ that is only slightly louder than a G.
+        let mut data = generate_tone(&[G.frequency(), B.frequency(), D.frequency()], 0.5, 44_100);
+        data.extend((0..22_050).map(|k| {
+            let t = k as f32 / 44_100.0;
+
+            [(E, 1.0), (B, 1.0), (GSharp, 0.75), (G, 0.7)]
+                .iter()
+                .map(|(n, amplitude)| amplitude * (2.0 * std::f32::consts::PI * n.frequency() * t).sin())
+                .sum::<f32>()
+        }));
+
+        let detect = |config: &AnalysisConfig| {
+            get_chords_from_audio_data_windowed_with_config(&data, 44_100, 0.5, 0.5, config)
+                .into_iter()
+                .map(|(_, c)| c.name())
+                .collect::<Vec<_>>()
+        };
+
+        let config = AnalysisConfig::default().with_backend(Backend::ChromaTemplate);
+
+        // Without the prior, the borderline chord is E major; with it, the G major pulls it toward the diatonic Em.
+        assert_eq!(detect(&config), vec!["G", "E"]);
+        assert_eq!(detect(&config.clone().with_temporal_prior(0.05)), vec!["G", "Em"]);
+    }
+
+    #[test]
+    fn test_temporal_prior_peaks() {
+        // A minor, followed by A, C, E, and G with the C in the bass (which reads as both C6, and Am7/C).
+        let mut data = generate_tone(&[A.frequency(), CFive.frequency(), EFive.frequency()], 1.0, 44_100);
+        data.extend(generate_tone(&[C.frequency(), E.frequency(), G.frequency(), A.frequency()], 1.0, 44_100));
+
+        let detect = |config: &AnalysisConfig| {
+            get_chords_from_audio_data_windowed_with_config(&data, 44_100, 1.0, 1.0, config)
+                .into_iter()
+                .map(|(_, c)| c.name())
+                .collect::<Vec<_>>()
+        };
+
+        let config = AnalysisConfig::default().with_backend(Backend::Peaks);
+
+        // Without the prior, the C in the bass makes it C6; with it, the A minor keeps it on A.
+        assert_eq!(detect(&config), vec!["Am", "C(add6)"]);
+        assert_eq!(detect(&config.clone().with_temporal_prior(0.5)), vec!["Am", "Am7/C"]);
+    }
+
+    #[test]
+    fn test_transcribe_with_beats() {
+        // A progression at 120 bpm, with each chord struck (and decaying) on each of its two beats.
+        let data = ["C", "C", "G", "G", "Am", "Am", "F", "F"]
+            .iter()
+            .flat_map(|chord| {
+                let frequencies = Chord::parse(chord).unwrap().chord().iter().map(|n| n.frequency()).collect::<Vec<_>>();
+
+                generate_tone(&frequencies, 0.5, 44_100).into_iter().enumerate().map(|(k, s)| s * (-4.0 * k as f32 / 44_100.0).exp())
+            })
+            .collect::<Vec<_>>();
+
+        let (beats, chords) = transcribe_with_beats(&data, 44_100);
+
+        assert_eq!(beats.len(), 8);
+
+        for (k, beat) in beats.iter().enumerate() {
+            assert!((beat - k as f32 * 0.5).abs() < 0.02, "Beat {k} was at {beat}.");
+        }
+
+        // Each chord starts on (one of) the beats.
+        assert_eq!(chords.iter().map(|(_, c)| c.name()).collect::<Vec<_>>(), vec!["C", "G", "Am", "F"]);
+
+        for ((time, _), expected) in chords.iter().zip([0, 2, 4, 6]) {
+            assert_eq!(*time, beats[expected]);
+        }
+    }
+
+    #[test]
+    fn test_dominant_chord_short() {
+        assert_eq!(dominant_chord(&[0.0; 100], 44100), None);
+    }
+}
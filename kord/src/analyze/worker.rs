@@ -0,0 +1,136 @@
+//! Background note detection.
+//!
+//! Runs detection on a worker thread, and pushes the results through an [`std::sync::mpsc`] channel, which is the usual way
+//! to feed a GUI (e.g., `egui` or `iced`) without blocking the UI thread.
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{channel, Receiver},
+        Arc,
+    },
+    thread::JoinHandle,
+};
+
+use crate::core::{
+    note::Note,
+    pitch::{set_tuning, tuning},
+};
+
+use super::base::{get_notes_with_magnitudes_from_audio_data, AnalysisConfig};
+
+// Struct.
+
+/// A handle to a background note detection thread.
+///
+/// The worker stops when [`DetectionWorker::stop`] is called, when the handle is dropped, when its source runs out, or
+/// when the receiving end of its channel is dropped.
+#[derive(Debug)]
+pub struct DetectionWorker {
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl DetectionWorker {
+    /// Spawns a worker that runs detection on each window yielded by `source` (each `length_in_seconds` long).
+    ///
+    /// Returns the worker handle, and a receiver of (note, confidence) updates, with one update per window.  The confidence
+    /// is the magnitude of the note relative to the strongest note in the window (i.e., in `(0, 1]`).
+    pub fn spawn<S>(source: S, length_in_seconds: f32) -> (Self, Receiver<Vec<(Note, f32)>>)
+    where
+        S: IntoIterator<Item = Vec<f32>>,
+        S::IntoIter: Send + 'static,
+    {
+        let (sender, receiver) = channel();
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let thread_stop = stop.clone();
+        let mut source = source.into_iter();
+
+        // The tuning is per thread, so carry the caller's over to the worker.
+        let tuning = tuning();
+
+        let handle = std::thread::spawn(move || {
+            set_tuning(tuning);
+
+            let config = AnalysisConfig::default();
+
+            while !thread_stop.load(Ordering::Relaxed) {
+                let Some(window) = source.next() else {
+                    break;
+                };
+
+                // A window that cannot be analyzed is treated as silence, so the worker keeps going.
+                let notes = get_notes_with_magnitudes_from_audio_data(&window, length_in_seconds, &config).unwrap_or_default();
+
+                if sender.send(notes).is_err() {
+                    break;
+                }
+            }
+        });
+
+        (Self { stop, handle: Some(handle) }, receiver)
+    }
+
+    /// Signals the worker to stop, and waits for it to finish.
+    ///
+    /// The worker finishes the window it is currently analyzing (if any) before stopping.
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    /// Returns whether or not the worker thread has finished.
+    pub fn is_finished(&self) -> bool {
+        self.handle.as_ref().map(|h| h.is_finished()).unwrap_or(true)
+    }
+
+    fn shutdown(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for DetectionWorker {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{helpers::generate_tone, note::A, pitch::HasFrequency};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_detection_worker() {
+        let window = generate_tone(&[A.frequency()], 0.5, 44_100);
+
+        // An endless source, so the worker only stops when signaled.
+        let (worker, receiver) = DetectionWorker::spawn(std::iter::repeat(window), 0.5);
+
+        let update = receiver.recv().unwrap();
+        assert_eq!(update.len(), 1);
+        assert_eq!(update[0].0, A);
+        assert_eq!(update[0].1, 1.0);
+
+        worker.stop();
+
+        // Once stopped, the channel drains and disconnects.
+        while receiver.recv().is_ok() {}
+    }
+
+    #[test]
+    fn test_detection_worker_exhausted_source() {
+        let (worker, receiver) = DetectionWorker::spawn(vec![generate_tone(&[A.frequency()], 0.5, 44_100)], 0.5);
+
+        assert_eq!(receiver.iter().count(), 1);
+
+        worker.stop();
+    }
+}
@@ -75,7 +75,7 @@ enum Command {
         bpm: f32,
     },
 
-    /// Attempt to guess the chord from a set of notes (ordered by simplicity).
+    /// Attempt to guess the chord from a set of notes (ordered by how well they fit).
     Guess {
         /// A set of notes from which the guesser will attempt to build a chord.
         notes: Vec<String>,
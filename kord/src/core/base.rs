@@ -7,6 +7,8 @@ use std::time::Duration;
 #[cfg(feature = "audio")]
 use rodio::{OutputStream, OutputStreamHandle, Sink};
 
+use crate::core::named_pitch::Spelling;
+
 /// Global result type.
 pub type Res<T> = anyhow::Result<T>;
 
@@ -15,6 +17,66 @@ pub type Err = anyhow::Error;
 
 /// Global void type.
 pub type Void = Res<()>;
+
+/// The errors that the library returns, so that callers can branch on them (rather than on their messages).
+///
+/// These are returned within the global [`Err`] type (and convert into it with `?`), so they are recovered with
+/// [`anyhow::Error::downcast_ref`]:
+///
+/// ```
+/// use klib::core::{
+///     base::{KordError, Parsable},
+///     chord::Chord,
+/// };
+///
+/// let error = Chord::parse("X").unwrap_err();
+///
+/// assert!(matches!(
+///     error.downcast_ref::<KordError>(),
+///     Some(KordError::ParseError(_))
+/// ));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KordError {
+    /// The listening length is too short to analyze (i.e., less than 0.2 seconds).
+    ListeningLengthTooShort,
+    /// An argument is out of range (e.g., a hop length of zero, or a channel layout with no channels).
+    InvalidArgument(String),
+    /// There is no input device with the requested name (or no default input device, if `requested` is `None`).
+    NoInputDevice {
+        /// The name of the requested input device.
+        requested: Option<String>,
+        /// The names of the available input devices.
+        available: Vec<String>,
+    },
+    /// The audio stream could not be opened, or failed.
+    StreamError(String),
+    /// The input could not be parsed.
+    ParseError(String),
+    /// The model could not be loaded, or run.
+    InferenceError(String),
+    /// The operation was cancelled (e.g., a microphone capture, before it captured anything).
+    Cancelled,
+    /// The input device did not deliver any audio within the given time.
+    Timeout(Duration),
+}
+
+impl std::fmt::Display for KordError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KordError::ListeningLengthTooShort => write!(f, "Listening length in seconds must be greater than 0.2."),
+            KordError::NoInputDevice { requested: None, .. } => write!(f, "Failed to get default input device."),
+            KordError::NoInputDevice { requested: Some(name), available } => {
+                write!(f, "Could not find an input device named `{name}` (available devices: {}).", available.join(", "))
+            }
+            KordError::InvalidArgument(message) | KordError::StreamError(message) | KordError::ParseError(message) | KordError::InferenceError(message) => write!(f, "{message}"),
+            KordError::Cancelled => write!(f, "The operation was cancelled."),
+            KordError::Timeout(timeout) => write!(f, "The input device did not deliver any audio within {:.1} seconds.", timeout.as_secs_f32()),
+        }
+    }
+}
+
+impl std::error::Error for KordError {}
 // Traits.
 
 /// A trait for types that have a static name.
@@ -29,6 +91,12 @@ pub trait HasName {
     fn name(&self) -> String;
 }
 
+/// A trait for types that have a computed name, which can be rendered with a chosen enharmonic spelling.
+pub trait HasSpelledName {
+    /// Returns the computed name of the type, with its accidentals spelled by the given preference.
+    fn name_with_spelling(&self, spelling: Spelling) -> String;
+}
+
 /// A trait for types that have a computed name.
 pub trait HasPreciseName {
     /// Returns the computed name of the type.
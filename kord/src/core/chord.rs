@@ -8,15 +8,19 @@ use serde::{Deserialize, Serialize};
 use pest::Parser;
 
 use crate::core::{
-    base::{HasDescription, HasName, HasPreciseName, HasStaticName, Parsable, Res},
+    base::{HasDescription, HasName, HasPreciseName, HasSpelledName, HasStaticName, KordError, Parsable, Res},
+    guitar::{guitar_voicings, Tuning, Voicing},
+    helpers::generate_tone,
     interval::Interval,
     known_chord::{HasRelativeChord, HasRelativeScale, KnownChord},
-    modifier::{known_modifier_sets, likely_extension_sets, one_off_modifier_sets, Degree, Extension, HasIsDominant, Modifier},
-    named_pitch::HasNamedPitch,
-    note::{CZero, Note, NoteRecreator},
+    modifier::{known_modifier_sets, likely_extension_sets, one_off_modifier_sets, Degree, Extension, HasIsAddedTone, HasIsDominant, Modifier},
+    named_pitch::{HasNamedPitch, NamedPitch, Spelling},
+    note::{CZero, HasNoteId, Note, NoteRecreator},
     octave::{HasOctave, Octave},
     parser::{note_str_to_note, octave_str_to_octave, ChordParser, Rule},
-    pitch::HasFrequency,
+    pitch::{HasFrequency, HasPitch, Pitch},
+    progression::functional_label,
+    scale::{Scale, ScaleKind},
 };
 
 // Traits.
@@ -45,6 +49,15 @@ pub trait HasExtensions {
     fn extensions(&self) -> &HashSet<Extension>;
 }
 
+/// A trait that represents a type that has added tones.
+pub trait HasAddedTones {
+    /// Returns the added tones of the implementor (most likely a [`Chord`]).
+    ///
+    /// Added tones (e.g., the `add9` in `Cadd9`) are added on top of the chord without implying a seventh,
+    /// unlike dominant extensions (e.g., the `9` in `C9`).
+    fn added_tones(&self) -> Vec<Extension>;
+}
+
 /// A trait that represents a type that has an inversion.
 pub trait HasInversion {
     /// Returns the inversion of the implementor (most likely a [`Chord`]).
@@ -234,6 +247,76 @@ pub trait HasDomninantDegree {
     fn dominant_degree(&self) -> Option<Degree>;
 }
 
+// Statics.
+
+/// The modifiers that are named as alterations (i.e., in parentheses, after the chord's quality), in the order that they are named.
+static ALTERATIONS: [(Modifier, &str); 6] = [
+    (Modifier::Flat5, "(♭5)"),
+    (Modifier::Augmented5, "(♯5)"),
+    (Modifier::Flat9, "(♭9)"),
+    (Modifier::Sharp9, "(♯9)"),
+    (Modifier::Sharp11, "(♯11)"),
+    (Modifier::Flat13, "(♭13)"),
+];
+
+// Enum.
+
+/// How octave-equivalent duplicate notes (the same pitch class in several octaves) are treated when guessing a chord from notes.
+#[derive(PartialEq, Eq, Copy, Clone, Hash, Debug, Default)]
+pub enum DuplicateHandling {
+    /// The notes must match the chord's notes exactly, octaves included (this is what [`Chord::try_from_notes`] does).
+    #[default]
+    Exact,
+    /// Duplicate pitch classes are collapsed into one; the lowest note is kept as the bass, and the others are brought into
+    /// close position above it before matching.
+    Collapse,
+}
+
+/// The inversion of a chord (i.e., which chord tone, in stacking order, is in the bass).
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(PartialEq, Eq, Copy, Clone, Hash, Debug, Default, PartialOrd, Ord)]
+#[repr(u8)]
+pub enum Inversion {
+    /// The root is in the bass.
+    #[default]
+    Root,
+    /// The third is in the bass.
+    First,
+    /// The fifth is in the bass.
+    Second,
+    /// The seventh (or sixth) is in the bass.
+    Third,
+    /// The ninth is in the bass.
+    Fourth,
+    /// The eleventh is in the bass.
+    Fifth,
+    /// The thirteenth is in the bass.
+    Sixth,
+}
+
+impl TryFrom<u8> for Inversion {
+    type Error = &'static str;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Inversion::Root),
+            1 => Ok(Inversion::First),
+            2 => Ok(Inversion::Second),
+            3 => Ok(Inversion::Third),
+            4 => Ok(Inversion::Fourth),
+            5 => Ok(Inversion::Fifth),
+            6 => Ok(Inversion::Sixth),
+            _ => Err("Invalid inversion."),
+        }
+    }
+}
+
+impl From<Inversion> for u8 {
+    fn from(value: Inversion) -> Self {
+        value as u8
+    }
+}
+
 // Struct.
 
 /// The primary chord struct.
@@ -278,8 +361,10 @@ impl Ord for Chord {
             let result = a_extensions_len.cmp(&b_extensions_len);
 
             if result.is_eq() {
-                let a_extensions = Vec::from_iter(&self.extensions);
-                let b_extensions = Vec::from_iter(&other.extensions);
+                let mut a_extensions = Vec::from_iter(&self.extensions);
+                let mut b_extensions = Vec::from_iter(&other.extensions);
+                a_extensions.sort();
+                b_extensions.sort();
 
                 a_extensions.cmp(&b_extensions)
             } else {
@@ -293,8 +378,10 @@ impl Ord for Chord {
             let result = a_modifiers_len.cmp(&b_modifiers_len);
 
             if result.is_eq() {
-                let a_modifiers = Vec::from_iter(&self.modifiers);
-                let b_modifiers = Vec::from_iter(&other.modifiers);
+                let mut a_modifiers = Vec::from_iter(&self.modifiers);
+                let mut b_modifiers = Vec::from_iter(&other.modifiers);
+                a_modifiers.sort();
+                b_modifiers.sort();
 
                 a_modifiers.cmp(&b_modifiers)
             } else {
@@ -315,6 +402,9 @@ impl Ord for Chord {
         let b_root = other.root;
         let cmp_root = a_root.cmp(&b_root);
 
+        // Fall back to the slash note itself, and then the canonical symbol, so that the order is always deterministic.
+        let cmp_slash = self.slash.cmp(&other.slash);
+
         cmp_all_changes
             .then(cmp_inversion)
             .then(cmp_slashes)
@@ -322,6 +412,8 @@ impl Ord for Chord {
             .then(cmp_modifiers)
             .then(cmp_root)
             .then(cmp_crunchy)
+            .then(cmp_slash)
+            .then_with(|| self.name().cmp(&other.name()))
     }
 }
 
@@ -343,22 +435,393 @@ impl Chord {
             is_crunchy: false,
         }
     }
+
+    /// Returns this chord, transposed by the given number of semitones (negative values transpose down).
+    ///
+    /// The root and slash are transposed (see [`Note::transpose`]), while the quality, extensions, inversion, and crunchiness
+    /// are preserved.
+    pub fn transpose(&self, semitones: i8) -> Chord {
+        Chord {
+            root: self.root.transpose(semitones),
+            slash: self.slash.map(|n| n.transpose(semitones)),
+            ..self.clone()
+        }
+    }
+
+    /// Returns this chord, with its root and slash respelled by the given function (which should keep their pitches).
+    pub(crate) fn respell_with(&self, respell: impl Fn(Note) -> Note) -> Chord {
+        Chord {
+            root: respell(self.root),
+            slash: self.slash.map(&respell),
+            ..self.clone()
+        }
+    }
+
+    /// Returns the functional label of this chord in the given key (major, or minor if `is_minor`), including its quality,
+    /// extensions, and inversion (e.g., `V7/V`, or `ii°6`).
+    ///
+    /// See [`functional_label`] for the notation.
+    pub fn relative_to(&self, key: Note, is_minor: bool) -> String {
+        let kind = if is_minor { ScaleKind::NaturalMinor } else { ScaleKind::Major };
+
+        functional_label(self, &Scale::new(key, kind))
+    }
+
+    /// Synthesizes the chord (i.e., each of its tones, as a sine wave) to a buffer of samples of the given length (in seconds),
+    /// and sample rate.
+    ///
+    /// The tones are voiced as they are when the chord is played (see [`Chord::playback_voicing`]).  See [`generate_tone`] for
+    /// the envelope, and normalization.
+    pub fn to_samples(&self, duration_seconds: f32, sample_rate: u32) -> Vec<f32> {
+        let frequencies = self.playback_voicing().iter().map(|n| n.frequency()).collect::<Vec<_>>();
+
+        generate_tone(&frequencies, duration_seconds, sample_rate)
+    }
+
+    /// Returns the inversion of this chord that places the given chord tone in the bass.
+    ///
+    /// Errors if the note is not a chord tone.
+    pub fn inversion_with_bass(&self, bass: Note) -> Res<Chord> {
+        let inversion = self
+            .relative_chord()
+            .into_iter()
+            .map(|i| (self.root + i).pitch())
+            .position(|p| p == bass.pitch())
+            .ok_or_else(|| anyhow::Error::msg(format!("{} is not a chord tone of {}.", bass.static_name(), self.name())))?;
+
+        Ok(self.clone().with_inversion(inversion as u8))
+    }
+
+    /// Returns the frequency of each chord tone, with the chord rooted in the given octave (e.g., `4` for a C4 root).
+    ///
+    /// Panics if the octave is not within `0..=15`.
+    pub fn frequencies(&self, base_octave: i8) -> Vec<f32> {
+        self.clone().with_octave(Octave::Zero + base_octave).chord().into_iter().map(|n| n.frequency()).collect()
+    }
+
+    /// Returns the neo-Riemannian (Tonnetz) neighbors of this chord: its parallel, leading-tone exchange, and relative triads, in that order.
+    ///
+    /// Only major and minor triads have neighbors, so any other chord returns an empty vector.
+    pub fn tonnetz_neighbors(&self) -> Vec<Chord> {
+        let root = self.root;
+
+        match self.known_chord() {
+            KnownChord::Major => vec![
+                Chord::new(root).minor(),
+                Chord::new(root + Interval::MajorThird).minor(),
+                Chord::new(root + Interval::MajorSixth).minor(),
+            ],
+            KnownChord::Minor => vec![Chord::new(root), Chord::new(root + Interval::MinorSixth), Chord::new(root + Interval::MinorThird)],
+            _ => vec![],
+        }
+    }
+
+    /// Returns a voicing of this chord that is suitable for playback.
+    ///
+    /// The root is placed an octave below the chord's octave, with the fifth and seventh just above it, and the third
+    /// lifted up an octave (an "open" voicing), so that the quality of the chord is clear without the low end getting muddy.
+    /// Extensions keep their (compound) intervals above the root, so a ninth sits just below the third, and an eleventh, or
+    /// thirteenth, above it.  If the chord is inverted, or has a slash, its bass note is placed below the root.
+    /// Notes are never placed below the lowest octave (i.e., the voicing of a chord that is already that low is not lowered).
+    pub fn playback_voicing(&self) -> Vec<Note> {
+        // An octave lower, clamped to the lowest octave.
+        let lower = |octave: Octave| Octave::try_from((octave as u8).saturating_sub(1)).unwrap_or_default();
+
+        let root = self.root.with_octave(lower(self.root.octave()));
+        let bass = self.chord()[0];
+
+        let mut result: Vec<_> = self
+            .relative_chord()
+            .into_iter()
+            .map(|i| match i {
+                Interval::MinorThird | Interval::MajorThird => root + i + Interval::PerfectOctave,
+                _ => root + i,
+            })
+            .collect();
+
+        if bass.pitch() != root.pitch() {
+            result.retain(|n| n.pitch() != bass.pitch());
+            result.push(Note::new(bass.named_pitch(), lower(root.octave())));
+        }
+
+        result.sort();
+        result.dedup();
+
+        result
+    }
+
+    /// Returns the playable voicings of this chord on a guitar in standard tuning, with open shapes (where available) first.
+    ///
+    /// See [`guitar_voicings`] for the search.
+    pub fn guitar_voicings(&self) -> Vec<Voicing> {
+        guitar_voicings(self, Tuning::Standard)
+    }
+
+    /// Returns a close-position voicing of this chord for piano: the bass note in the third octave, with the other chord tones
+    /// in the octave above it, and any extensions (i.e., tones more than an octave above the root) stacked on top, in order.
+    pub fn piano_voicing(&self) -> Vec<Note> {
+        let tones = self.chord();
+        let bass = Note::new(tones[0].named_pitch(), Octave::Three);
+        let root = self.root.id_index();
+
+        let above = |floor: Note, tone: Note| {
+            let mut note = Note::new(tone.named_pitch(), floor.octave());
+
+            while note.id_index() <= floor.id_index() {
+                note += Interval::PerfectOctave;
+            }
+
+            note
+        };
+
+        let mut result = vec![bass];
+
+        let (extensions, chord_tones): (Vec<_>, Vec<_>) = tones.into_iter().skip(1).partition(|n| n.id_index() >= root + 12);
+
+        for tone in chord_tones {
+            if result.iter().all(|n| n.pitch() != tone.pitch()) {
+                result.push(above(bass, tone));
+            }
+        }
+
+        result.sort();
+
+        for tone in extensions {
+            if result.iter().all(|n| n.pitch() != tone.pitch()) {
+                result.push(above(result[result.len() - 1], tone));
+            }
+        }
+
+        result
+    }
+
+    /// Returns the pitch-class set of this chord (i.e., the distinct pitch classes of its tones, where `0` is C), sorted.
+    pub fn pitch_class_set(&self) -> Vec<u8> {
+        let mut result = self.chord().into_iter().map(|n| n.pitch() as u8).collect::<Vec<_>>();
+
+        result.sort();
+        result.dedup();
+
+        result
+    }
 }
 
 impl Chord {
+    /// Attempts to name the given pitch-class set (i.e., pitch classes in `0..12`, where `0` is C) as a chord.
+    ///
+    /// Each pitch class is tried as the root, with the other pitch classes voiced above it (tensions are voiced in the
+    /// octave above, when the chord has a third or a seventh to support them).  The candidates are ordered by simplicity
+    /// (i.e., least slashes, least extensions, least modifiers, and least inversion).
+    pub fn from_pitch_class_set(set: &[u8]) -> Res<Vec<Self>> {
+        if let Some(pitch_class) = set.iter().find(|pc| **pc >= 12) {
+            return Err(anyhow::Error::msg(format!("{pitch_class} is not a valid pitch class.")));
+        }
+
+        let mut set = set.to_vec();
+        set.sort();
+        set.dedup();
+
+        if set.len() < 3 {
+            return Err(anyhow::Error::msg("Must have at least three pitch classes to guess a chord."));
+        }
+
+        let mut result = Vec::new();
+
+        for root in &set {
+            let intervals = set.iter().map(|pc| (pc + 12 - root) % 12).collect::<Vec<_>>();
+            let has_third = intervals.contains(&3) || intervals.contains(&4);
+            let has_fifth = intervals.contains(&7);
+            let has_seventh = intervals.contains(&10) || intervals.contains(&11);
+
+            // In a "close" voicing, every pitch class is within an octave of the root; in an "extended" voicing, the
+            // pitch classes that are more likely to be tensions are lifted into the next octave.
+            let is_tension = |semitones: u8| match semitones {
+                1 | 2 => true,
+                5 => has_third,
+                6 | 8 => has_fifth,
+                9 => has_seventh,
+                _ => false,
+            };
+
+            let has_tension = intervals.iter().any(|semitones| is_tension(*semitones));
+
+            let voicings: &[bool] = if has_tension { &[false, true] } else { &[false] };
+
+            for extended in voicings {
+                let root_note = Note::from_pitch(Pitch::try_from(*root).map_err(anyhow::Error::msg)?, 4);
+                let notes = intervals
+                    .iter()
+                    .map(|semitones| {
+                        let semitones = *semitones as usize + if *extended && is_tension(*semitones) { 12 } else { 0 };
+
+                        Note::from_id(root_note.id() << semitones)
+                    })
+                    .collect::<Res<Vec<_>>>()?;
+
+                result.extend(Self::candidates_from_notes(&notes)?.into_iter().filter(|c| c.pitch_class_set() == set));
+            }
+        }
+
+        result.sort();
+        result.dedup_by_key(|c| c.name());
+
+        Ok(result)
+    }
+
+    /// Returns all of the equally valid names of the given notes (e.g., `C6` and `Am7` for A, C, E, and G), ordered by simplicity.
+    ///
+    /// The notes are treated as a pitch-class set (see [`Chord::from_pitch_class_set`]).  For each possible root, the simplest
+    /// root-position reading is considered, and the readings with the fewest alterations (i.e., the parenthesized modifiers and
+    /// extensions that are not part of the chord's quality) are co-equal.  A sixth counts as part of the quality, like a seventh
+    /// does.  Symmetric chords (e.g., diminished sevenths) have a co-equal reading on each of their roots.
+    pub fn co_equal_interpretations(notes: &[Note]) -> Res<Vec<Self>> {
+        let set = notes.iter().map(|n| n.pitch() as u8).collect::<Vec<_>>();
+
+        let mut readings: Vec<Self> = Vec::new();
+
+        for chord in Self::from_pitch_class_set(&set)? {
+            if chord.inversion == 0 && chord.slash.is_none() && !readings.iter().any(|r| r.root.pitch() == chord.root.pitch()) {
+                readings.push(chord);
+            }
+        }
+
+        let Some(fewest) = readings.iter().map(|c| c.alteration_count()).min() else {
+            return Ok(readings);
+        };
+
+        readings.retain(|c| c.alteration_count() == fewest);
+
+        Ok(readings)
+    }
+
+    /// Returns the number of alterations of the chord (i.e., its modifiers that are not part of its quality, and its
+    /// extensions), not counting a sixth.
+    fn alteration_count(&self) -> usize {
+        let extensions = self.extensions.iter().filter(|e| **e != Extension::Add6).count();
+
+        self.alterations().len() + extensions
+    }
+
+    /// Returns the names of the modifiers of the chord that are not part of its quality (e.g., the `♭5` of a half diminished
+    /// chord is, but that of a dominant seventh is not), in the order that they are named.
+    fn alterations(&self) -> Vec<&'static str> {
+        let known_chord = self.known_chord();
+
+        let is_part_of_quality = |modifier: Modifier| match modifier {
+            Modifier::Flat5 => matches!(known_chord, KnownChord::HalfDiminished(_)),
+            Modifier::Augmented5 => matches!(known_chord, KnownChord::Augmented | KnownChord::AugmentedMajor7 | KnownChord::AugmentedDominant(_)),
+            Modifier::Flat9 => matches!(known_chord, KnownChord::DominantFlat9(_) | KnownChord::MinorDominantFlat9Flat13(_)),
+            Modifier::Sharp9 => matches!(known_chord, KnownChord::DominantSharp9(_)),
+            Modifier::Sharp11 => matches!(known_chord, KnownChord::DominantSharp11(_) | KnownChord::Sharp11),
+            Modifier::Flat13 => matches!(known_chord, KnownChord::MinorDominantFlat13(_) | KnownChord::MinorDominantFlat9Flat13(_)),
+            _ => true,
+        };
+
+        ALTERATIONS
+            .iter()
+            .filter(|(m, _)| self.modifiers.contains(m) && !is_part_of_quality(*m))
+            .map(|(_, name)| *name)
+            .collect()
+    }
+
+    /// Attempts to guess the chord from the given MIDI note numbers (see [`Note::from_midi`]), returning the best candidate.
+    ///
+    /// Octave-equivalent duplicates (e.g., a doubled root) are collapsed (see [`DuplicateHandling::Collapse`]).  Returns an
+    /// error if any of the numbers are out of range, or if no chord is found.
+    pub fn from_midi(notes: &[u8]) -> Res<Self> {
+        let notes = notes.iter().map(|n| Note::from_midi(*n)).collect::<Res<Vec<_>>>()?;
+
+        match Self::try_from_notes_with_duplicate_handling(&notes, DuplicateHandling::Collapse)?.into_iter().next() {
+            Some(chord) => Ok(chord),
+            None => Err(anyhow::Error::msg("Could not find a chord for the MIDI notes.")),
+        }
+    }
+
+    /// Attempts to guess the chord from the notes, treating octave-equivalent duplicates as specified.
+    pub fn try_from_notes_with_duplicate_handling(notes: &[Note], duplicate_handling: DuplicateHandling) -> Res<Vec<Self>> {
+        match duplicate_handling {
+            DuplicateHandling::Exact => Self::try_from_notes(notes),
+            DuplicateHandling::Collapse => {
+                let Some(bass) = notes.iter().min().copied() else {
+                    return Self::try_from_notes(notes);
+                };
+
+                let mut collapsed = vec![bass];
+
+                for note in notes {
+                    if collapsed.iter().any(|n| n.pitch() == note.pitch()) {
+                        continue;
+                    }
+
+                    let mut note = note.with_octave(bass.octave());
+
+                    if note < bass {
+                        note = note.with_octave(bass.octave() + 1);
+                    }
+
+                    collapsed.push(note);
+                }
+
+                Self::try_from_notes(&collapsed)
+            }
+        }
+    }
+
+    /// Attempts to guess the chord from the notes, returning every candidate with its fit score, best first.
+    ///
+    /// Each candidate is scored against the distinct pitches of the notes, relative to its root: each matched chord tone (the
+    /// root, the third, the fifth, and the seventh) counts `1`, each missing third or fifth costs `0.5`, and each extra tone
+    /// (e.g., an added sixth, or an alteration) costs `0.25`.  The root is strengthened by `1` if it is in the bass, and an
+    /// inversion penalty is subtracted (`0.5` per inversion, and `0.5` for a slash).  Candidates with the same score keep the
+    /// order of simplicity (i.e., least slashes, least extensions, least modifiers, and least inversion).  Returns no
+    /// candidates if none are found (e.g., for fewer than three notes).
+    pub fn try_from_notes_ranked(notes: &[Note]) -> Vec<(Self, f32)> {
+        let pitches = notes.iter().map(|n| n.pitch() as u8).collect::<HashSet<_>>();
+        let Some(bass) = notes.iter().min() else {
+            return Vec::new();
+        };
+
+        let mut result = Self::candidates_from_notes(notes)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|chord| {
+                let score = fit_score(&chord, &pitches, bass.pitch() as u8);
+
+                (chord, score)
+            })
+            .collect::<Vec<_>>();
+
+        // The sort is stable, so ties stay in order of simplicity.
+        result.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap_or(Ordering::Equal));
+
+        result
+    }
+
     /// Attempts to guess the chord from the notes.
+    ///
+    /// The candidates are ordered by how well they fit the notes (see [`Chord::try_from_notes_ranked`] for the scores).
     pub fn try_from_notes(notes: &[Note]) -> Res<Vec<Self>> {
         if notes.len() < 3 {
             return Err(anyhow::Error::msg("Must have at least three notes to guess a chord."));
         }
 
+        Ok(Self::try_from_notes_ranked(notes).into_iter().map(|(chord, _)| chord).collect())
+    }
+
+    /// Finds every chord whose notes are exactly the given notes, ordered by simplicity.
+    fn candidates_from_notes(notes: &[Note]) -> Res<Vec<Self>> {
+        if notes.len() < 3 {
+            return Err(anyhow::Error::msg("Must have at least three notes to guess a chord."));
+        }
+
         let mut notes = notes.to_vec();
         notes.sort();
 
         let mut result = Vec::new();
 
-        // Iterate through all known chords (and some likely extensions) and find the longest match.
-        for inversion in 0..3 {
+        // Iterate through all known chords (and some likely extensions) and find the longest match, in each inversion (up to
+        // the seventh in the bass).
+        for inversion in 0..notes.len().min(4) {
             let proper_root = if inversion == 0 {
                 notes[0]
             } else {
@@ -446,6 +909,10 @@ impl Chord {
         // Remove duplicates (and ignore crunchy; i.e., `C7` and `C7!` should be treated as "the same").
         result.dedup_by(|a, b| a.modifiers == b.modifiers && a.extensions == b.extensions && a.slash == b.slash && a.inversion == b.inversion);
 
+        // An inversion above a slash note only revoices the upper notes, so keep the simplest of the readings that share a name.
+        let mut names = HashSet::new();
+        result.retain(|c| names.insert(c.name()));
+
         Ok(result)
     }
 }
@@ -462,40 +929,27 @@ impl HasName for Chord {
 
         // Add special modifiers that are true modifiers when not part of their "special case".
 
-        if self.modifiers.contains(&Modifier::Flat5) && !known_name.contains("(♭5)") {
-            name.push_str("(♭5)");
-        }
-
-        if self.modifiers.contains(&Modifier::Augmented5) && !known_name.contains('+') {
-            name.push_str("(♯5)");
-        }
-
-        if self.modifiers.contains(&Modifier::Flat9) && !known_name.contains("(♭9)") {
-            name.push_str("(♭9)");
-        }
-
-        if self.modifiers.contains(&Modifier::Sharp9) && !known_name.contains("(♯9)") {
-            name.push_str("(♯9)");
-        }
-
-        if self.modifiers.contains(&Modifier::Sharp11) && !known_name.contains("(♯11)") {
-            name.push_str("(♯11)");
+        for alteration in self.alterations() {
+            name.push_str(alteration);
         }
 
-        if self.modifiers.contains(&Modifier::Flat13) && !known_name.contains("(♭13)") {
-            name.push_str("(♭13)");
-        }
-
-        // Add extensions.
+        // Add extensions (in a stable order).
         if !self.extensions.is_empty() {
-            for e in &self.extensions {
+            let mut extensions = Vec::from_iter(&self.extensions);
+            extensions.sort();
+
+            for e in extensions {
                 name.push_str(&format!("({})", e.static_name()));
             }
         }
 
-        // Add slash note.
+        // Add slash note (or, for an inversion, the chord tone in the bass).
         if let Some(slash) = self.slash {
             name.push_str(&format!("/{}", slash.static_name()));
+        } else if self.inversion != 0 {
+            if let Some(bass) = self.chord().first() {
+                name.push_str(&format!("/{}", bass.static_name()));
+            }
         }
 
         // Add special information about the chord.
@@ -504,11 +958,43 @@ impl HasName for Chord {
     }
 }
 
+impl HasSpelledName for Chord {
+    /// Returns the name of the chord, with its root (and bass) spelled by the given preference.
+    ///
+    /// With [`Spelling::Auto`], the root is spelled by the key that the chord implies on the circle of fifths (e.g., `D♭`,
+    /// but `C♯m`, since C♯ minor is the relative minor of E major), and the bass follows the root's accidentals.
+    fn name_with_spelling(&self, spelling: Spelling) -> String {
+        let relative_chord = self.relative_chord();
+        let is_minor = relative_chord.contains(&Interval::MinorThird) && !relative_chord.contains(&Interval::MajorThird);
+
+        let root = match spelling {
+            Spelling::Auto if is_minor => self.root.respell_as(self.root.named_pitch().respell_from(NamedPitch::EFlat)),
+            _ => self.root.respell(spelling),
+        };
+
+        // The bass follows the key of the root (i.e., whether it is sharp or flat of C major).
+        let key_position = root.named_pitch() as i8 - NamedPitch::C as i8 - if is_minor { 3 } else { 0 };
+
+        let bass_spelling = match spelling {
+            Spelling::Auto if key_position > 0 => Spelling::Sharps,
+            Spelling::Auto if key_position < 0 => Spelling::Flats,
+            _ => spelling,
+        };
+
+        let mut chord = self.clone();
+        chord.root = root;
+        chord.slash = self.slash.map(|s| s.respell(bass_spelling));
+
+        chord.name()
+    }
+}
+
 impl HasPreciseName for Chord {
     fn precise_name(&self) -> String {
         let mut name = String::new();
 
-        name.push_str(&self.name());
+        // The inversion is expressed below, so leave out its bass note.
+        name.push_str(&self.clone().with_inversion(0).name());
 
         // Add octave modifier.
         if self.root.octave() != Octave::Four {
@@ -553,6 +1039,15 @@ impl HasExtensions for Chord {
     }
 }
 
+impl HasAddedTones for Chord {
+    fn added_tones(&self) -> Vec<Extension> {
+        let mut result = self.extensions.iter().filter(|e| e.is_added_tone()).copied().collect::<Vec<_>>();
+        result.sort();
+
+        result
+    }
+}
+
 impl HasInversion for Chord {
     fn inversion(&self) -> u8 {
         self.inversion
@@ -1109,12 +1604,17 @@ impl HasDomninantDegree for Chord {
     }
 }
 
+/// Chord symbols are parsed from the grammar in `chord.pest`.
+///
+/// A slash is disambiguated by what follows it: a note is a bass note (e.g., `C7/G` is a C7 over G), while a tension
+/// (a number, or a modifier like `b9`) is an added tone (e.g., `C7/9` is `C7(9)`, which is `C7add9`).  Bare tension
+/// numbers are only allowed in parentheses or after a slash, since `C79` would otherwise be ambiguous.
 impl Parsable for Chord {
     fn parse(input: &str) -> Res<Self>
     where
         Self: Sized,
     {
-        let root = ChordParser::parse(Rule::chord, input)?.next().unwrap();
+        let root = ChordParser::parse(Rule::chord, input).map_err(|e| KordError::ParseError(e.to_string()))?.next().unwrap();
 
         assert_eq!(Rule::chord, root.as_rule());
 
@@ -1213,6 +1713,22 @@ impl Parsable for Chord {
                         unreachable!();
                     }
                 },
+                Rule::tension => match component.as_str() {
+                    "9" => {
+                        result = result.add9();
+                    }
+                    "11" => {
+                        result = result.add11();
+                    }
+                    "13" => {
+                        result = result.add13();
+                    }
+                    _ => {
+                        unreachable!();
+                    }
+                },
+                // The tension itself is the next component.
+                Rule::tension_slash => {}
                 Rule::slash => {
                     let note = note_str_to_note(components.next().unwrap().as_str())?;
 
@@ -1251,7 +1767,7 @@ impl Playable for Chord {
     fn play(&self, delay: Duration, length: Duration, fade_in: Duration) -> Res<PlaybackHandle> {
         use rodio::{source::SineWave, OutputStream, Sink, Source};
 
-        let chord_tones = self.chord();
+        let chord_tones = self.playback_voicing();
 
         if length.as_secs_f32() <= chord_tones.len() as f32 * delay.as_secs_f32() {
             return Err(anyhow::Error::msg(
@@ -1285,6 +1801,37 @@ impl Default for Chord {
     }
 }
 
+// Helpers.
+
+/// Scores how well the chord fits the pitch classes (see [`Chord::try_from_notes_ranked`]).
+fn fit_score(chord: &Chord, pitches: &HashSet<u8>, bass: u8) -> f32 {
+    let root = chord.root.pitch() as u8;
+    let intervals = pitches.iter().map(|p| (p + 12 - root) % 12).collect::<HashSet<_>>();
+
+    let third = [3, 4].into_iter().find(|i| intervals.contains(i));
+    let fifth = match third {
+        _ if intervals.contains(&7) => Some(7),
+        Some(3) if intervals.contains(&6) => Some(6),
+        Some(4) if intervals.contains(&8) => Some(8),
+        _ => None,
+    };
+    let seventh = match fifth {
+        Some(6) if intervals.contains(&9) => Some(9),
+        _ => [10, 11].into_iter().find(|i| intervals.contains(i)),
+    };
+
+    let chord_tones = [Some(0), third, fifth, seventh].into_iter().flatten().collect::<Vec<_>>();
+
+    let matched = chord_tones.len() as f32;
+    let missing = third.is_none() as u8 as f32 + fifth.is_none() as u8 as f32;
+    let extra = (intervals.len() - chord_tones.len()) as f32;
+
+    let root_strength = if root == bass { 1.0 } else { 0.0 };
+    let inversion_penalty = 0.5 * chord.inversion as f32 + if chord.slash.is_some() { 0.5 } else { 0.0 };
+
+    matched - 0.5 * missing - 0.25 * extra + root_strength - inversion_penalty
+}
+
 // Tests.
 
 #[cfg(test)]
@@ -1476,6 +2023,68 @@ mod tests {
         assert_eq!(Chord::parse("D(#13)").unwrap().chord(), vec![D, FSharp, A, BSharpFive]);
     }
 
+    #[test]
+    fn test_parse_slash_tensions() {
+        let c7add9 = Chord::parse("C7add9").unwrap();
+
+        // Tensions, whether after a slash or in parentheses, are added tones.
+        assert_eq!(Chord::parse("C7(9)").unwrap(), c7add9);
+        assert_eq!(Chord::parse("C7/9").unwrap(), c7add9);
+        assert_eq!(Chord::parse("C7/b9").unwrap(), Chord::parse("C7b9").unwrap());
+        assert_eq!(Chord::parse("C7(13)").unwrap().chord(), vec![C, E, G, BFlat, AFive]);
+        assert_eq!(Chord::parse("C/9").unwrap(), Chord::parse("Cadd9").unwrap());
+
+        // Notes after a slash are bass notes.
+        assert_eq!(Chord::parse("C7/G").unwrap().slash(), G);
+        assert_eq!(Chord::parse("C7/G").unwrap().chord(), vec![GThree, C, E, G, BFlat]);
+
+        // Both at once: the tension comes first.
+        let chord = Chord::parse("C7/9/E").unwrap();
+
+        assert_eq!(chord.slash(), E);
+        assert!(chord.extensions().contains(&Extension::Add9));
+
+        // Bare tensions need parentheses (or a slash).
+        assert!(Chord::parse("C79").is_err());
+    }
+
+    #[test]
+    fn test_six_nine_and_add_combinations() {
+        let c69 = Chord::parse("C6/9").unwrap();
+
+        assert_eq!(c69.chord(), vec![C, E, G, A, DFive]);
+        assert_eq!(Chord::parse("C6(9)").unwrap(), c69);
+        assert_eq!(Chord::parse("Cm6/9").unwrap().chord(), vec![C, EFlat, G, A, DFive]);
+        assert_eq!(Chord::parse("Cadd9/11").unwrap().chord(), vec![C, E, G, DFive, FFive]);
+
+        // Detection agrees with the parser.
+        for name in ["C6/9", "Cm6/9", "Cadd9/11"] {
+            let chord = Chord::parse(name).unwrap();
+
+            assert_eq!(Chord::try_from_notes(&chord.chord()).unwrap()[0], chord, "{name}");
+        }
+    }
+
+    #[test]
+    fn test_added_tones() {
+        let add9 = Chord::parse("Cadd9").unwrap();
+        let nine = Chord::parse("C9").unwrap();
+
+        assert_eq!(add9.added_tones(), vec![Extension::Add9]);
+        assert_eq!(add9.dominant_degree(), None);
+        assert!(!add9.chord().contains(&BFlat));
+
+        assert_eq!(nine.added_tones(), vec![]);
+        assert_eq!(nine.dominant_degree(), Some(Degree::Nine));
+        assert!(nine.chord().contains(&BFlat));
+
+        assert_eq!(add9.chord(), vec![C, E, G, DFive]);
+        assert_eq!(nine.chord(), vec![C, E, G, BFlat, DFive]);
+
+        assert_eq!(Chord::parse("C7sus4add13").unwrap().added_tones(), vec![Extension::Add13]);
+        assert_eq!(Chord::new(C).add6().add9().sharp13().added_tones(), vec![Extension::Add6, Extension::Add9]);
+    }
+
     #[test]
     fn test_guess() {
         assert_eq!(
@@ -1494,9 +2103,381 @@ mod tests {
         assert_eq!(Chord::try_from_notes(&[C, EFlat, GFlat, A]).unwrap().first().unwrap().chord(), Chord::parse("Cdim").unwrap().chord());
     }
 
+    #[test]
+    fn test_inversion_with_bass() {
+        assert_eq!(Chord::new(C).inversion_with_bass(C).unwrap().inversion(), 0);
+        assert_eq!(Chord::new(C).inversion_with_bass(ETwo).unwrap().inversion(), 1);
+        assert_eq!(Chord::new(C).inversion_with_bass(G).unwrap().inversion(), 2);
+        assert_eq!(Chord::new(C).inversion_with_bass(G).unwrap().chord(), vec![G, CFive, EFive]);
+        assert_eq!(Chord::new(C).seven().inversion_with_bass(BFlat).unwrap().inversion(), 3);
+        assert_eq!(Chord::new(C).minor().inversion_with_bass(DSharp).unwrap().inversion(), 1);
+    }
+
+    #[test]
+    fn test_inversion_names() {
+        let guess = |notes: &[Note]| {
+            let chord = Chord::try_from_notes(notes).unwrap().remove(0);
+
+            (chord.name(), chord.inversion())
+        };
+
+        // Inversions are named (and detected) by their bass note.
+        assert_eq!(guess(&[EThree, GThree, C]), ("C/E".to_string(), 1));
+        assert_eq!(guess(&[GThree, C, E]), ("C/G".to_string(), 2));
+        assert_eq!(guess(&[BThree, D, F, G]), ("G7/B".to_string(), 1));
+
+        // Third inversions are candidates, too.
+        let candidates = Chord::try_from_notes(&[FThree, GThree, BThree, D]).unwrap();
+        assert!(candidates.iter().any(|c| c.name() == "G7/F" && c.inversion() == 3));
+
+        // A bass note that is not a chord tone is a slash.
+        assert_eq!(guess(&[DThree, C, E, G]), ("C/D".to_string(), 0));
+        assert_eq!(guess(&[FSharpThree, C, E, G]), ("C/F♯".to_string(), 0));
+
+        // The precise name expresses the inversion, so that it round trips.
+        let chord = Chord::new(C).seven().with_inversion(1);
+
+        assert_eq!(chord.name(), "C7/E");
+        assert_eq!(chord.precise_name(), "C7^1");
+        assert_eq!(Chord::parse(&chord.precise_name()).unwrap(), chord);
+
+        // The name round trips, too: it parses as the slash chord over the same bass note, which has the same name.
+        for chord in [
+            Chord::new(C).with_inversion(1),
+            Chord::new(C).minor().with_inversion(2),
+            Chord::new(G).seven().with_inversion(3),
+            Chord::new(C).with_slash(D),
+        ] {
+            let parsed = Chord::parse(&chord.name()).unwrap();
+
+            assert_eq!(parsed.name(), chord.name());
+            assert_eq!(parsed.chord()[0].pitch(), chord.chord()[0].pitch(), "{}", chord.name());
+            assert_eq!(parsed.pitch_class_set(), chord.pitch_class_set(), "{}", chord.name());
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "D is not a chord tone of C.")]
+    fn test_inversion_with_bass_failure() {
+        Chord::new(C).inversion_with_bass(D).unwrap();
+    }
+
     #[test]
     #[should_panic(expected = "Must have at least three notes to guess a chord.")]
     fn test_chord_from_notes_failure() {
         Chord::try_from_notes(&[C, E]).unwrap();
     }
+
+    #[test]
+    fn test_try_from_notes_ranked() {
+        let ranked = |notes: &[Note]| Chord::try_from_notes_ranked(notes).into_iter().map(|(c, s)| (c.name(), s)).collect::<Vec<_>>();
+
+        // A, C, E, and G are both Am7 and C6, and the bass decides which fits better.
+        let minor = ranked(&[AThree, C, E, G]);
+
+        assert_eq!(minor[0], ("Am7".to_string(), 5.0));
+        assert!(minor.contains(&("C/A".to_string(), 2.25)));
+
+        let major = ranked(&[C, E, G, A]);
+
+        assert_eq!(major[0], ("C(add6)".to_string(), 3.75));
+        assert!(major.contains(&("Am7/C".to_string(), 3.5)));
+
+        // An inversion is preferred over an altered chord (with a missing fifth, and an extra tone) in root position.
+        assert_eq!(ranked(&[E, G, CFive]), vec![("C/E".to_string(), 2.5), ("Em(♯5)".to_string(), 2.25)]);
+
+        // A complete chord over a bass note is preferred over the simplest reading (a suspended chord with an extra tone).
+        let complete = ranked(&[FThree, GThree, BThree, D]);
+
+        assert_eq!(complete[0], ("G/F".to_string(), 3.5));
+        assert_eq!(complete.last().unwrap(), &("Fdim(sus2)".to_string(), 0.25));
+
+        // The scores are in order, and the unranked guesses are in the same order.
+        assert!(major.windows(2).all(|w| w[0].1 >= w[1].1));
+        assert_eq!(
+            Chord::try_from_notes(&[FThree, GThree, BThree, D]).unwrap().into_iter().map(|c| c.name()).collect::<Vec<_>>(),
+            complete.into_iter().map(|(name, _)| name).collect::<Vec<_>>()
+        );
+
+        assert_eq!(ranked(&[C, E]), vec![]);
+    }
+
+    #[test]
+    fn test_name_with_spelling() {
+        let name = |name: &str, spelling: Spelling| Chord::parse(name).unwrap().name_with_spelling(spelling);
+
+        assert_eq!(name("C#m", Spelling::Sharps), "C♯m");
+        assert_eq!(name("C#m", Spelling::Flats), "D♭m");
+        assert_eq!(name("Dbm", Spelling::Sharps), "C♯m");
+        assert_eq!(name("A#7", Spelling::Flats), "B♭7");
+        assert_eq!(name("C/Bb", Spelling::Sharps), "C/A♯");
+
+        // By the circle of fifths, with minor chords spelled by their relative major.
+        assert_eq!(name("C#", Spelling::Auto), "D♭");
+        assert_eq!(name("Dbm", Spelling::Auto), "C♯m");
+        assert_eq!(name("D#m7", Spelling::Auto), "E♭m7");
+        assert_eq!(name("Abm", Spelling::Auto), "G♯m");
+        assert_eq!(name("Gb", Spelling::Auto), "F♯");
+
+        // The bass follows the root.
+        assert_eq!(name("C#/G#", Spelling::Auto), "D♭/A♭");
+        assert_eq!(name("E/Ab", Spelling::Auto), "E/G♯");
+        assert_eq!(name("C/Bb", Spelling::Auto), "C/B♭");
+    }
+
+    #[test]
+    fn test_piano_voicing() {
+        let voicing = |name: &str| Chord::parse(name).unwrap().piano_voicing();
+
+        assert_eq!(voicing("C"), vec![CThree, EThree, GThree]);
+        assert_eq!(voicing("Am7"), vec![AThree, C, E, G]);
+        assert_eq!(voicing("C/E"), vec![EThree, GThree, C]);
+        assert_eq!(voicing("C7/G"), vec![GThree, BFlatThree, C, E]);
+
+        // Extensions are stacked above the chord tones.
+        assert_eq!(voicing("C13"), vec![CThree, EThree, GThree, BFlatThree, D, F, A]);
+    }
+
+    #[test]
+    fn test_playback_voicing() {
+        assert_eq!(Chord::new(C).playback_voicing(), vec![CThree, GThree, E]);
+        assert_eq!(Chord::parse("Cmaj7").unwrap().playback_voicing(), vec![CThree, GThree, BThree, E]);
+        assert_eq!(Chord::parse("Cm7").unwrap().playback_voicing(), vec![CThree, GThree, BFlatThree, EFlat]);
+        assert_eq!(Chord::parse("C9").unwrap().playback_voicing(), vec![CThree, GThree, BFlatThree, D, E]);
+        assert_eq!(Chord::parse("C13").unwrap().playback_voicing(), vec![CThree, GThree, BFlatThree, D, E, F, A]);
+        assert_eq!(Chord::parse("C/E").unwrap().playback_voicing(), vec![ETwo, CThree, GThree]);
+        assert_eq!(Chord::parse("C/B").unwrap().playback_voicing(), vec![BTwo, CThree, GThree, E]);
+
+        // Roots in the lowest octaves are clamped, rather than lowered out of range.
+        let (c_zero, e_one, g_zero, b_zero) = (C.with_octave(Octave::Zero), E.with_octave(Octave::One), G.with_octave(Octave::Zero), B.with_octave(Octave::Zero));
+
+        assert_eq!(Chord::new(C.with_octave(Octave::One)).playback_voicing(), vec![c_zero, g_zero, e_one]);
+        assert_eq!(Chord::new(c_zero).playback_voicing(), vec![c_zero, g_zero, e_one]);
+        assert_eq!(
+            Chord::new(C.with_octave(Octave::One)).with_slash(B.with_octave(Octave::One)).playback_voicing(),
+            vec![c_zero, g_zero, b_zero, e_one]
+        );
+        assert_eq!(Chord::new(C.with_octave(Octave::One)).to_samples(0.1, 44_100).len(), 4_410);
+    }
+
+    #[test]
+    #[cfg(feature = "analyze_base")]
+    fn test_playback_voicing_round_trip() {
+        use crate::analyze::base::get_notes_from_audio_data;
+
+        for (name, known_chord) in [
+            ("C", KnownChord::Major),
+            ("Cm", KnownChord::Minor),
+            ("Cmaj7", KnownChord::Major7),
+            ("Cm7", KnownChord::MinorDominant(Degree::Seven)),
+            ("C7", KnownChord::Dominant(Degree::Seven)),
+        ] {
+            let voicing = Chord::parse(name).unwrap().playback_voicing();
+            let frequencies = voicing.iter().map(|n| n.frequency()).collect::<Vec<_>>();
+
+            let mut notes = get_notes_from_audio_data(&generate_tone(&frequencies, 1.0, 44_100), 1.0).unwrap();
+            notes.sort();
+            assert_eq!(notes, voicing);
+
+            // Bring the open voicing back into close position above the bass before guessing.
+            let bass = notes[0];
+            let closed = notes
+                .into_iter()
+                .map(|mut n| {
+                    while n.frequency() >= bass.frequency() * 2.0 {
+                        n = n.with_octave(n.octave() - 1);
+                    }
+
+                    n
+                })
+                .collect::<Vec<_>>();
+
+            let chord = Chord::try_from_notes(&closed).unwrap().into_iter().next().unwrap();
+            assert_eq!(chord.known_chord(), known_chord, "Failed to recover the quality of {name}.");
+        }
+    }
+
+    #[test]
+    fn test_tonnetz_neighbors() {
+        assert_eq!(Chord::new(C).tonnetz_neighbors(), vec![Chord::new(C).minor(), Chord::new(E).minor(), Chord::new(A).minor()]);
+        assert_eq!(Chord::new(C).minor().tonnetz_neighbors(), vec![Chord::new(C), Chord::new(AFlat), Chord::new(EFlat)]);
+        assert_eq!(Chord::new(C).seven().tonnetz_neighbors(), vec![]);
+    }
+
+    #[test]
+    fn test_deterministic_order() {
+        let names = || Chord::try_from_notes(&[C, E, G, BFlat, DFive]).unwrap().into_iter().map(|c| c.precise_name()).collect::<Vec<_>>();
+        let first = names();
+
+        for _ in 0..10 {
+            assert_eq!(names(), first);
+        }
+
+        // Extensions are named in a stable order, regardless of the order they were added in.
+        assert_eq!(Chord::new(C).add13().add9().add11().name(), "C(add9)(add11)(add13)");
+    }
+
+    #[test]
+    fn test_duplicate_handling() {
+        let collapsed = |notes: &[Note]| Chord::try_from_notes_with_duplicate_handling(notes, DuplicateHandling::Collapse).unwrap().into_iter().next().unwrap();
+
+        let doubled = [CThree, GThree, C, E, G, CFive];
+        assert_eq!(collapsed(&doubled), Chord::new(CThree));
+        assert!(Chord::try_from_notes_with_duplicate_handling(&doubled, DuplicateHandling::Exact).unwrap().is_empty());
+
+        assert_eq!(collapsed(&[CThree, BThree, E, G, CFive, EFive]).known_chord(), KnownChord::Major7);
+
+        // The lowest note is kept as the bass.
+        assert_eq!(collapsed(&[EThree, GThree, C, E, CFive]).chord()[0], EThree);
+    }
+
+    #[test]
+    fn test_from_midi() {
+        assert_eq!(Chord::from_midi(&[60, 64, 67]).unwrap(), Chord::new(C));
+        assert_eq!(Chord::from_midi(&[57, 60, 64, 67, 69]).unwrap().name(), "Am7");
+        assert_eq!(Chord::from_midi(&[52, 55, 60]).unwrap().name(), "C/E");
+
+        assert!(Chord::from_midi(&[60, 64]).is_err());
+        assert!(Chord::from_midi(&[60, 64, 200]).is_err());
+    }
+
+    #[test]
+    fn test_frequencies() {
+        let frequencies = Chord::new(C).frequencies(4);
+        let expected = [261.63, 329.63, 392.00];
+
+        assert_eq!(frequencies.len(), expected.len());
+        for (frequency, expected) in frequencies.iter().zip(expected) {
+            assert!((frequency - expected).abs() < 0.05, "Expected {expected}, got {frequency}.");
+        }
+
+        assert_eq!(Chord::new(A).minor().frequencies(3), vec![AThree.frequency(), C.frequency(), E.frequency()]);
+    }
+
+    #[test]
+    fn test_pitch_class_set() {
+        assert_eq!(Chord::new(C).pitch_class_set(), vec![0, 4, 7]);
+        assert_eq!(Chord::new(A).minor().seven().pitch_class_set(), vec![0, 4, 7, 9]);
+        assert_eq!(Chord::new(D).seven().flat9().pitch_class_set(), vec![0, 2, 3, 6, 9]);
+
+        for name in ["C", "Am", "G7", "Fmaj7", "D9"] {
+            let chord = Chord::parse(name).unwrap();
+            let candidates = Chord::from_pitch_class_set(&chord.pitch_class_set()).unwrap();
+
+            assert_eq!(candidates[0].name(), chord.name());
+        }
+
+        // Some sets are ambiguous (e.g., Bm7(♭5) and Dm6 share a set), but all of the readings are candidates.
+        for (name, other) in [("Bm7(♭5)", "Dm(add6)"), ("G(sus4)", "C(sus2)")] {
+            let candidates = Chord::from_pitch_class_set(&Chord::parse(name).unwrap().pitch_class_set()).unwrap();
+
+            assert!(candidates.iter().any(|c| c.name() == name));
+            assert!(candidates.iter().any(|c| c.name() == other));
+        }
+
+        assert!(Chord::from_pitch_class_set(&[0, 4]).is_err());
+        assert!(Chord::from_pitch_class_set(&[0, 4, 12]).is_err());
+    }
+
+    #[test]
+    fn test_co_equal_interpretations() {
+        let names = |notes: &[Note]| Chord::co_equal_interpretations(notes).unwrap().iter().map(|c| c.name()).collect::<Vec<_>>();
+
+        assert_eq!(names(&[A, C, E, G]), vec!["C(add6)", "Am7"]);
+        assert_eq!(names(&[C, EFlat, GFlat, A]), vec!["Cdim", "E♭dim", "G♭dim", "Adim"]);
+
+        // Unambiguous sets only have one reading.
+        assert_eq!(names(&[C, E, G, B]), vec!["Cmaj7"]);
+
+        // Modifiers that are part of the quality are not alterations.
+        let alterations = |name: &str| Chord::parse(name).unwrap().alteration_count();
+
+        assert_eq!(alterations("Cm7b5"), 0);
+        assert_eq!(alterations("C+7"), 0);
+        assert_eq!(alterations("C7b9"), 0);
+        assert_eq!(alterations("C6"), 0);
+        assert_eq!(alterations("C7(b5)"), 1);
+        assert_eq!(alterations("Cm7(#5)"), 1);
+        assert_eq!(alterations("C7b9(#11)(add13)"), 2);
+    }
+
+    #[test]
+    fn test_relative_to() {
+        let label = |chord: &str, key: Note, is_minor: bool| Chord::parse(chord).unwrap().relative_to(key, is_minor);
+
+        // Diatonic chords, with sevenths, extensions, and inversions.
+        assert_eq!(label("C", C, false), "I");
+        assert_eq!(label("Dm7", C, false), "ii7");
+        assert_eq!(label("G7", C, false), "V7");
+        assert_eq!(label("G9", C, false), "V9");
+        assert_eq!(label("Bm7b5", C, false), "viiø7");
+        assert_eq!(label("C/E", C, false), "I6");
+        assert_eq!(label("C/G", C, false), "I64");
+        assert_eq!(label("G7/B", C, false), "V65");
+        assert_eq!(label("G7/D", C, false), "V43");
+        assert_eq!(label("G7/F", C, false), "V42");
+
+        // Minor keys.
+        assert_eq!(label("Bm(b5)/D", A, true), "ii°6");
+        assert_eq!(label("E7", A, true), "V7");
+        assert_eq!(label("C+", A, true), "III+");
+
+        // Secondary functions, and chromatic chords.
+        assert_eq!(label("D7", C, false), "V7/V");
+        assert_eq!(label("A7/C#", C, false), "V65/ii");
+        assert_eq!(label("F#dim", C, false), "vii°7/V");
+        assert_eq!(label("Bb", C, false), "♭VII");
+        assert_eq!(label("Fm", C, false), "iv");
+    }
+
+    #[test]
+    fn test_transpose() {
+        let transpose = |chord: &str, semitones: i8| Chord::parse(chord).unwrap().transpose(semitones).name();
+
+        assert_eq!(transpose("Cmaj7", 2), "Dmaj7");
+        assert_eq!(transpose("Am7/G", 3), "Cm7/B♭");
+        assert_eq!(transpose("C7b9", -2), "B♭7(♭9)");
+        assert_eq!(transpose("Bm7b5", 1), "Cm7(♭5)");
+
+        let chord = Chord::parse("Cmaj7").unwrap().with_inversion(1).with_crunchy(true);
+        assert_eq!(chord.transpose(2), Chord::parse("Dmaj7").unwrap().with_inversion(1).with_crunchy(true));
+        assert_eq!(
+            chord.transpose(2).chord().iter().map(|n| n.frequency()).collect::<Vec<_>>(),
+            chord.chord().iter().map(|n| n.transpose(2).frequency()).collect::<Vec<_>>()
+        );
+        assert_eq!(chord.transpose(5).transpose(-5), chord);
+    }
+
+    #[test]
+    #[cfg(feature = "analyze_base")]
+    fn test_to_samples() {
+        use crate::analyze::base::get_notes_from_audio_data;
+
+        for name in ["C", "Am7", "G7", "Fmaj7"] {
+            let chord = Chord::parse(name).unwrap();
+            let samples = chord.to_samples(1.0, 44_100);
+
+            assert_eq!(samples.len(), 44_100);
+
+            // The samples hold the tones of the playback voicing.
+            let mut notes = get_notes_from_audio_data(&samples, 1.0).unwrap();
+            notes.sort();
+
+            assert_eq!(notes, chord.playback_voicing(), "{name}");
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_serde() {
+        for name in ["C", "C7b9", "Am7/G", "C7b9#11", "Bm7b5", "C/E", "F#m(b5)/A", "Cmaj7!"] {
+            let chord = Chord::parse(name).unwrap();
+            let json = serde_json::to_string(&chord).unwrap();
+
+            assert_eq!(serde_json::from_str::<Chord>(&json).unwrap(), chord, "{json}");
+        }
+
+        let chord = Chord::parse("C").unwrap().with_inversion(1);
+        assert_eq!(serde_json::from_str::<Chord>(&serde_json::to_string(&chord).unwrap()).unwrap(), chord);
+    }
 }
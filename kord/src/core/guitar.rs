@@ -0,0 +1,328 @@
+//! A module for working with guitars.
+
+use std::fmt::Display;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::core::{
+    base::HasStaticName,
+    chord::{Chord, HasChord, HasRoot},
+    interval::Interval,
+    note::{
+        AFlatTwo, AThree, ATwo, BFlatThree, BThree, BTwo, CThree, CTwo, DFlatThree, DThree, DTwo, EFlat, EFlatTwo, EThree, ETwo, FSharpThree, FThree, GFlatThree, GSharpThree, GThree, GTwo, HasNoteId,
+        Note, D, E,
+    },
+    pitch::{HasPitch, Pitch},
+};
+
+// Statics.
+
+/// The highest fret that voicings are searched up to.
+pub static MAX_FRET: u8 = 12;
+
+/// The largest stretch (in frets) between the lowest and highest fretted notes of a voicing (i.e., a four fret span).
+pub static MAX_STRETCH: u8 = 3;
+
+/// The fewest strings that a voicing rings.
+pub static MIN_STRINGS: usize = 4;
+
+/// The number of fingers available to fret notes (i.e., not counting the thumb).
+static NUM_FINGERS: usize = 4;
+
+/// All of the known tunings, in order of how common they are.
+pub static ALL_TUNINGS: [Tuning; 8] = [
+    Tuning::Standard,
+    Tuning::DropD,
+    Tuning::HalfStepDown,
+    Tuning::DropC,
+    Tuning::OpenG,
+    Tuning::OpenD,
+    Tuning::OpenE,
+    Tuning::Dadgad,
+];
+
+// Enum.
+
+/// A (six string) guitar tuning.
+#[derive(PartialEq, Eq, Copy, Clone, Hash, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Tuning {
+    /// Standard tuning (E A D G B E).
+    #[default]
+    Standard,
+    /// Drop D tuning (D A D G B E).
+    DropD,
+    /// Half step down tuning (E♭ A♭ D♭ G♭ B♭ E♭).
+    HalfStepDown,
+    /// Drop C tuning (C G C F A D).
+    DropC,
+    /// Open G tuning (D G D G B D).
+    OpenG,
+    /// Open D tuning (D A D F♯ A D).
+    OpenD,
+    /// Open E tuning (E B E G♯ B E).
+    OpenE,
+    /// DADGAD tuning (D A D G A D).
+    Dadgad,
+}
+
+impl Tuning {
+    /// Returns the open strings of the tuning, from the lowest string to the highest.
+    pub fn strings(&self) -> [Note; 6] {
+        match self {
+            Tuning::Standard => [ETwo, ATwo, DThree, GThree, BThree, E],
+            Tuning::DropD => [DTwo, ATwo, DThree, GThree, BThree, E],
+            Tuning::HalfStepDown => [EFlatTwo, AFlatTwo, DFlatThree, GFlatThree, BFlatThree, EFlat],
+            Tuning::DropC => [CTwo, GTwo, CThree, FThree, AThree, D],
+            Tuning::OpenG => [DTwo, GTwo, DThree, GThree, BThree, D],
+            Tuning::OpenD => [DTwo, ATwo, DThree, FSharpThree, AThree, D],
+            Tuning::OpenE => [ETwo, BTwo, EThree, GSharpThree, BThree, E],
+            Tuning::Dadgad => [DTwo, ATwo, DThree, GThree, AThree, D],
+        }
+    }
+}
+
+impl HasStaticName for Tuning {
+    fn static_name(&self) -> &'static str {
+        match self {
+            Tuning::Standard => "Standard",
+            Tuning::DropD => "Drop D",
+            Tuning::HalfStepDown => "Half Step Down",
+            Tuning::DropC => "Drop C",
+            Tuning::OpenG => "Open G",
+            Tuning::OpenD => "Open D",
+            Tuning::OpenE => "Open E",
+            Tuning::Dadgad => "DADGAD",
+        }
+    }
+}
+
+// Struct.
+
+/// A guitar voicing of a chord: the fret played on each of the six strings (from the lowest string to the highest), where
+/// `None` is a muted string, and `Some(0)` is an open string.
+#[derive(PartialEq, Eq, Copy, Clone, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Voicing {
+    /// The fret played on each string, from the lowest string to the highest.
+    pub frets: [Option<u8>; 6],
+}
+
+impl Voicing {
+    /// Creates a new voicing from the fret played on each string (from the lowest string to the highest).
+    pub fn new(frets: [Option<u8>; 6]) -> Self {
+        Self { frets }
+    }
+
+    /// Returns the notes that sound when this voicing is played in the given tuning, from the lowest string to the highest.
+    pub fn notes(&self, tuning: Tuning) -> Vec<Note> {
+        tuning.strings().into_iter().zip(self.frets).filter_map(|(string, fret)| Some(string.transpose(fret? as i8))).collect()
+    }
+
+    /// Returns whether or not this voicing rings any open strings.
+    pub fn is_open(&self) -> bool {
+        self.frets.contains(&Some(0))
+    }
+
+    /// Returns the number of strings that sound in this voicing.
+    pub fn num_strings(&self) -> usize {
+        self.frets.iter().flatten().count()
+    }
+
+    /// Returns the highest fret of this voicing (i.e., how far up the neck the hand is).
+    pub fn max_fret(&self) -> u8 {
+        self.frets.iter().flatten().copied().max().unwrap_or(0)
+    }
+
+    /// Returns whether or not this voicing is physically playable: the fretted notes fit within [`MAX_STRETCH`] frets, and
+    /// can be held by four fingers (where the index finger may barre the lowest fret).
+    pub fn is_playable(&self) -> bool {
+        let fretted = self.frets.iter().flatten().copied().filter(|f| *f > 0).collect::<Vec<_>>();
+
+        let (Some(lowest), Some(highest)) = (fretted.iter().min(), fretted.iter().max()) else {
+            return true;
+        };
+
+        highest - lowest <= MAX_STRETCH && (fretted.len() <= NUM_FINGERS || fretted.iter().filter(|f| *f > lowest).count() < NUM_FINGERS)
+    }
+
+    /// Returns whether or not this voicing is contained in the other voicing (i.e., the other voicing plays the same frets in
+    /// the same position, and rings more strings).
+    fn is_contained_in(&self, other: &Voicing) -> bool {
+        self != other && self.max_fret() == other.max_fret() && self.frets.iter().zip(other.frets).all(|(a, b)| a.is_none() || *a == b)
+    }
+}
+
+impl Display for Voicing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let separator = if self.max_fret() > 9 { "-" } else { "" };
+        let frets = self.frets.map(|f| f.map(|f| f.to_string()).unwrap_or("x".to_string()));
+
+        write!(f, "{}", frets.join(separator))
+    }
+}
+
+// Functions.
+
+/// Returns the playable voicings of the given chord in the given tuning, with the shapes lowest on the neck (i.e., open
+/// shapes, where available) first.
+///
+/// Every voicing rings a contiguous set of at least [`MIN_STRINGS`] strings (muting any strings on either side), has the
+/// chord's bass note as its lowest note, and covers every tone of the chord (except that the fifth may be left out of seventh
+/// chords, and larger).  Voicings that only mute strings of another voicing in the same position are left out.
+pub fn guitar_voicings(chord: &Chord, tuning: Tuning) -> Vec<Voicing> {
+    let tones = chord.chord();
+    let bass = tones[0].pitch();
+
+    let mut pitches = tones.iter().map(|n| n.pitch()).collect::<Vec<_>>();
+    pitches.sort();
+    pitches.dedup();
+
+    let fifth = (chord.root() + Interval::PerfectFifth).pitch();
+    let required = pitches.iter().copied().filter(|p| pitches.len() < 4 || *p != fifth).collect::<Vec<_>>();
+
+    let strings = tuning.strings();
+    let mut result = Vec::new();
+
+    for first in 0..strings.len() {
+        for last in (first + MIN_STRINGS - 1)..strings.len() {
+            search(&strings, last, &pitches, &mut [None; 6], first, &mut |frets| {
+                let voicing = Voicing::new(*frets);
+                let sounding = voicing.notes(tuning).into_iter().map(|n| n.pitch()).collect::<Vec<_>>();
+
+                if sounding[0] == bass && required.iter().all(|p| sounding.contains(p)) && voicing.is_playable() {
+                    result.push(voicing);
+                }
+            });
+        }
+    }
+
+    let mut result = result.iter().filter(|v| !result.iter().any(|o| v.is_contained_in(o))).copied().collect::<Vec<_>>();
+
+    result.sort_by_key(|v| (v.max_fret(), std::cmp::Reverse(v.num_strings()), v.frets.iter().flatten().filter(|f| **f > 0).count(), v.frets));
+
+    result
+}
+
+// Helpers.
+
+/// Enumerates every assignment of frets (up to [`MAX_FRET`]) to the strings from `string` to `last` that only plays the given
+/// pitches, pruning assignments that are not playable.
+fn search(strings: &[Note; 6], last: usize, pitches: &[Pitch], frets: &mut [Option<u8>; 6], string: usize, f: &mut impl FnMut(&[Option<u8>; 6])) {
+    if string > last {
+        f(frets);
+        return;
+    }
+
+    for fret in 0..=MAX_FRET {
+        if !pitches.contains(&strings[string].transpose(fret as i8).pitch()) {
+            continue;
+        }
+
+        frets[string] = Some(fret);
+
+        if Voicing::new(*frets).is_playable() {
+            search(strings, last, pitches, frets, string + 1, f);
+        }
+    }
+
+    frets[string] = None;
+}
+
+/// Returns the known tuning that best matches the given (detected) open-string notes.
+///
+/// Each distinct detected note scores two points if it is one of the tuning's open strings, or one point if it only matches
+/// one of them by pitch class (i.e., it was detected in the wrong octave).  Ties are broken in favor of the more common
+/// tuning (see [`ALL_TUNINGS`]).  Returns `None` if no tuning matches any of the notes.
+pub fn detect_guitar_tuning(notes: &[Note]) -> Option<Tuning> {
+    let mut notes = notes.iter().map(|n| n.id_index()).collect::<Vec<_>>();
+    notes.sort();
+    notes.dedup();
+
+    let mut best: Option<(Tuning, usize)> = None;
+
+    for tuning in ALL_TUNINGS {
+        let strings = tuning.strings().map(|n| n.id_index());
+
+        let score = notes
+            .iter()
+            .map(|note| {
+                if strings.contains(note) {
+                    2
+                } else if strings.iter().any(|s| s % 12 == note % 12) {
+                    1
+                } else {
+                    0
+                }
+            })
+            .sum::<usize>();
+
+        if score > 0 && best.map(|(_, best_score)| score > best_score).unwrap_or(true) {
+            best = Some((tuning, score));
+        }
+    }
+
+    best.map(|(tuning, _)| tuning)
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{base::Parsable, note::*};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_detect_guitar_tuning() {
+        for tuning in ALL_TUNINGS {
+            assert_eq!(detect_guitar_tuning(&tuning.strings()), Some(tuning), "{}", tuning.static_name());
+        }
+
+        // Drop D, with the strings strummed in any order, and a string detected twice.
+        assert_eq!(detect_guitar_tuning(&[E, DTwo, GThree, ATwo, BThree, DThree, DTwo]), Some(Tuning::DropD));
+
+        // Only the low strings were heard.
+        assert_eq!(detect_guitar_tuning(&[DTwo, ATwo]), Some(Tuning::DropD));
+
+        assert_eq!(detect_guitar_tuning(&[]), None);
+    }
+
+    #[test]
+    fn test_guitar_voicings() {
+        let first = |name: &str| Chord::parse(name).unwrap().guitar_voicings()[0].to_string();
+
+        // The open shapes come first.
+        assert_eq!(first("C"), "x32010");
+        assert_eq!(first("G"), "320003");
+        assert_eq!(first("D"), "xx0232");
+        assert_eq!(first("A"), "x02220");
+        assert_eq!(first("E"), "022100");
+        assert_eq!(first("Am"), "x02210");
+        assert_eq!(first("Dm"), "xx0231");
+        assert_eq!(first("C7"), "x32310");
+        assert_eq!(first("G7"), "320001");
+
+        // There is no open F, but there is the barre.
+        let f = Chord::parse("F").unwrap().guitar_voicings();
+
+        assert!(f.contains(&Voicing::new([Some(1), Some(3), Some(3), Some(2), Some(1), Some(1)])));
+        assert!(f.iter().all(|v| v.is_playable()));
+
+        // A slash chord has its bass note on the bottom.
+        assert_eq!(first("C/E"), "xx2010");
+
+        let voicing = Voicing::new([None, Some(3), Some(2), Some(0), Some(1), Some(0)]);
+
+        assert_eq!(voicing.notes(Tuning::Standard), vec![CThree, EThree, GThree, C, E]);
+        assert!(voicing.is_open());
+        assert_eq!(voicing.num_strings(), 5);
+
+        // Stretches, and too many fingers.
+        assert!(!Voicing::new([Some(1), Some(5), None, None, None, None]).is_playable());
+        assert!(!Voicing::new([None, Some(3), Some(4), Some(2), Some(3), Some(1)]).is_playable());
+
+        assert_eq!(Voicing::new([Some(8), Some(10), Some(10), Some(9), Some(8), Some(8)]).to_string(), "8-10-10-9-8-8");
+    }
+}
@@ -1,5 +1,17 @@
 //! Helper functions.
 
+use std::f32::consts::PI;
+
+// Statics.
+
+/// The length (in seconds) of the fade in, and fade out, of a [`generate_tone`], which keeps its edges from clicking.
+pub static TONE_FADE_IN_SECONDS: f32 = 0.01;
+
+/// The peak amplitude of a [`generate_tone`], which leaves plenty of headroom before clipping.
+pub static TONE_PEAK_AMPLITUDE: f32 = 0.5;
+
+// Functions.
+
 /// Converts a frequency to a mel.
 pub fn mel(f: f32) -> f32 {
     2595f32 * (1f32 + f / 700f32).log10()
@@ -9,3 +21,54 @@ pub fn mel(f: f32) -> f32 {
 pub fn inv_mel(m: f32) -> f32 {
     700f32 * (10f32.powf(m / 2595f32) - 1f32)
 }
+
+/// Generates a tone of the given length (in seconds), and sample rate, that sums sine waves at the given frequencies.
+///
+/// The tone fades in and out over [`TONE_FADE_IN_SECONDS`] (so that it does not click), and it is normalized to a peak of
+/// [`TONE_PEAK_AMPLITUDE`] (so that it does not clip, no matter how many frequencies are summed).
+pub fn generate_tone(frequencies: &[f32], length_in_seconds: f32, sample_rate: u32) -> Vec<f32> {
+    let sample_count = (length_in_seconds * sample_rate as f32) as usize;
+    let fade_count = ((TONE_FADE_IN_SECONDS * sample_rate as f32) as usize).min(sample_count / 2).max(1);
+
+    let mut result = (0..sample_count)
+        .map(|k| {
+            let t = k as f32 / sample_rate as f32;
+            let envelope = (k.min(sample_count - 1 - k) as f32 / fade_count as f32).min(1.0);
+
+            envelope * frequencies.iter().map(|f| (2.0 * PI * f * t).sin()).sum::<f32>()
+        })
+        .collect::<Vec<_>>();
+
+    let peak = result.iter().fold(0.0f32, |acc, s| acc.max(s.abs()));
+
+    if peak > 0.0 {
+        result.iter_mut().for_each(|s| *s *= TONE_PEAK_AMPLITUDE / peak);
+    }
+
+    result
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_generate_tone() {
+        let tone = generate_tone(&[261.63, 329.63, 392.0], 0.5, 44_100);
+        let peak = tone.iter().fold(0.0f32, |acc, s| acc.max(s.abs()));
+
+        assert_eq!(tone.len(), 22_050);
+        assert!((peak - TONE_PEAK_AMPLITUDE).abs() < 1e-6);
+
+        // The edges fade in and out.
+        assert_eq!(tone[0], 0.0);
+        assert_eq!(tone[tone.len() - 1], 0.0);
+        assert!(tone[..10].iter().all(|s| s.abs() < 0.01));
+
+        assert_eq!(generate_tone(&[], 0.5, 44_100), vec![0.0; 22_050]);
+        assert_eq!(generate_tone(&[440.0], 0.0, 44_100), vec![]);
+    }
+}
@@ -8,7 +8,10 @@ use wasm_bindgen::prelude::*;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
-use crate::core::octave::{HasOctave, Octave};
+use crate::core::{
+    base::HasName,
+    octave::{HasOctave, Octave},
+};
 
 // Traits.
 
@@ -152,8 +155,155 @@ pub enum Interval {
     ThreePerfectOctavesAndMajorSeventh,
 }
 
+/// The quality of a [`SpelledInterval`].
+#[derive(PartialEq, Eq, Copy, Clone, Hash, Debug, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum IntervalQuality {
+    /// A diminished interval, diminished the given number of times (e.g., `2` is doubly diminished).
+    Diminished(u8),
+    /// A minor interval.
+    Minor,
+    /// A perfect interval.
+    Perfect,
+    /// A major interval.
+    Major,
+    /// An augmented interval, augmented the given number of times (e.g., `2` is doubly augmented).
+    Augmented(u8),
+}
+
+// Struct.
+
+/// A spelled (i.e., diatonic) interval between two notes, counting octaves (e.g., a major tenth).
+///
+/// Unlike an [`Interval`], which only covers the intervals that appear in chords, any two notes have a [`SpelledInterval`]
+/// between them (see [`Note::interval_to`](crate::core::note::Note::interval_to)).
+#[derive(PartialEq, Eq, Copy, Clone, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SpelledInterval {
+    quality: IntervalQuality,
+    number: u8,
+    is_descending: bool,
+}
+
 // Impls.
 
+impl SpelledInterval {
+    /// Creates a new interval from its distance in diatonic steps (i.e., letter names, so a third is `2`), and in semitones.
+    ///
+    /// Negative distances are descending intervals.
+    pub fn from_distance(steps: i16, semitones: i16) -> Self {
+        let is_descending = steps < 0 || (steps == 0 && semitones < 0);
+        let (steps, semitones) = if is_descending { (-steps, -semitones) } else { (steps, semitones) };
+
+        let simple = (steps % 7) as usize;
+        let difference = semitones - (MAJOR_SCALE_SEMITONES[simple] + 12 * (steps / 7));
+
+        let quality = match (matches!(simple, 0 | 3 | 4), difference) {
+            (true, 0) => IntervalQuality::Perfect,
+            (false, 0) => IntervalQuality::Major,
+            (false, -1) => IntervalQuality::Minor,
+            (_, d) if d > 0 => IntervalQuality::Augmented(d as u8),
+            (true, d) => IntervalQuality::Diminished(-d as u8),
+            (false, d) => IntervalQuality::Diminished((-d - 1) as u8),
+        };
+
+        Self {
+            quality,
+            number: steps as u8 + 1,
+            is_descending,
+        }
+    }
+
+    /// Returns the quality of the interval.
+    pub fn quality(&self) -> IntervalQuality {
+        self.quality
+    }
+
+    /// Returns the number of the interval (e.g., `3` for a third, or `10` for a tenth), which counts both notes.
+    pub fn number(&self) -> u8 {
+        self.number
+    }
+
+    /// Returns whether the interval is descending (i.e., the second note is below the first).
+    pub fn is_descending(&self) -> bool {
+        self.is_descending
+    }
+
+    /// Returns the size of the interval in semitones (negative if it is descending).
+    pub fn semitones(&self) -> i16 {
+        let steps = self.number as i16 - 1;
+        let simple = (steps % 7) as usize;
+        let is_perfect = matches!(simple, 0 | 3 | 4);
+
+        let difference = match self.quality {
+            IntervalQuality::Perfect | IntervalQuality::Major => 0,
+            IntervalQuality::Minor => -1,
+            IntervalQuality::Augmented(n) => n as i16,
+            IntervalQuality::Diminished(n) if is_perfect => -(n as i16),
+            IntervalQuality::Diminished(n) => -(n as i16) - 1,
+        };
+
+        let semitones = MAJOR_SCALE_SEMITONES[simple] + 12 * (steps / 7) + difference;
+
+        if self.is_descending {
+            -semitones
+        } else {
+            semitones
+        }
+    }
+}
+
+impl HasName for SpelledInterval {
+    /// Returns the name of the interval (e.g., `minor third`, `major tenth`, or `perfect 19th`), which does not include its
+    /// direction.
+    fn name(&self) -> String {
+        let times = |n: u8| match n {
+            1 => String::new(),
+            2 => "doubly ".to_string(),
+            3 => "triply ".to_string(),
+            n => format!("{n}× "),
+        };
+
+        let quality = match self.quality {
+            IntervalQuality::Diminished(n) => format!("{}diminished", times(n)),
+            IntervalQuality::Minor => "minor".to_string(),
+            IntervalQuality::Perfect => "perfect".to_string(),
+            IntervalQuality::Major => "major".to_string(),
+            IntervalQuality::Augmented(n) => format!("{}augmented", times(n)),
+        };
+
+        let number = match self.number {
+            1 => "unison".to_string(),
+            2 => "second".to_string(),
+            3 => "third".to_string(),
+            4 => "fourth".to_string(),
+            5 => "fifth".to_string(),
+            6 => "sixth".to_string(),
+            7 => "seventh".to_string(),
+            8 => "octave".to_string(),
+            9 => "ninth".to_string(),
+            10 => "tenth".to_string(),
+            11 => "eleventh".to_string(),
+            12 => "twelfth".to_string(),
+            13 => "thirteenth".to_string(),
+            14 => "fourteenth".to_string(),
+            15 => "fifteenth".to_string(),
+            n if n % 10 == 1 && n % 100 != 11 => format!("{n}st"),
+            n if n % 10 == 2 && n % 100 != 12 => format!("{n}nd"),
+            n if n % 10 == 3 && n % 100 != 13 => format!("{n}rd"),
+            n => format!("{n}th"),
+        };
+
+        format!("{quality} {number}")
+    }
+}
+
+impl Display for SpelledInterval {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(f, "{}", self.name())
+    }
+}
+
 impl HasEnharmonicDistance for Interval {
     fn enharmonic_distance(&self) -> i8 {
         match self {
@@ -369,6 +519,9 @@ impl Display for Interval {
 
 // Statics.
 
+/// The number of semitones above the tonic of each degree of the major scale (i.e., of the major and perfect intervals).
+static MAJOR_SCALE_SEMITONES: [i16; 7] = [0, 2, 4, 5, 7, 9, 11];
+
 /// All known [`Interval`]s.
 pub static ALL_INTERVALS: [Interval; 48] = [
     Interval::PerfectUnison,
@@ -0,0 +1,244 @@
+//! A module for working with MIDI events.
+
+use std::collections::BTreeMap;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::core::{
+    chord::{Chord, DuplicateHandling},
+    note::Note,
+};
+
+// Statics.
+
+/// The default timing tolerance (in ticks) within which MIDI events are considered simultaneous.
+pub static DEFAULT_MIDI_TIMING_TOLERANCE: u32 = 10;
+
+/// The MIDI key of the lowest piano key (A0).
+pub static PIANO_LOWEST_MIDI_KEY: u8 = 21;
+
+/// The number of keys on a piano (A0 through C8).
+pub static PIANO_NUM_KEYS: usize = 88;
+
+// Enum.
+
+/// A (parsed) MIDI channel message.
+#[derive(PartialEq, Eq, Copy, Clone, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum MidiMessage {
+    /// A note-on message.  A velocity of `0` is treated as a note-off, per the MIDI specification.
+    NoteOn {
+        /// The channel (`0` through `15`).
+        channel: u8,
+        /// The key (e.g., `60` for C4).
+        key: u8,
+        /// The velocity.
+        velocity: u8,
+    },
+    /// A note-off message.
+    NoteOff {
+        /// The channel (`0` through `15`).
+        channel: u8,
+        /// The key (e.g., `60` for C4).
+        key: u8,
+        /// The release velocity.
+        velocity: u8,
+    },
+}
+
+// Struct.
+
+/// A note that sounds for a span of time (e.g., a detected note, or a note-on / note-off pair).
+#[derive(PartialEq, Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NoteEvent {
+    /// The note.
+    pub note: Note,
+    /// The start time (in seconds).
+    pub start: f32,
+    /// The duration (in seconds).
+    pub duration: f32,
+}
+
+impl NoteEvent {
+    /// Creates a new note event.
+    pub fn new(note: Note, start: f32, duration: f32) -> Self {
+        Self { note, start, duration }
+    }
+
+    /// Returns the end time (in seconds).
+    pub fn end(&self) -> f32 {
+        self.start + self.duration
+    }
+}
+
+// Functions.
+
+/// Gets the timestamped chords from a sequence of timed (ticks, message) MIDI events.
+///
+/// Events that are within [`DEFAULT_MIDI_TIMING_TOLERANCE`] ticks of each other are considered simultaneous.
+pub fn chords_from_midi_events(events: &[(u32, MidiMessage)]) -> Vec<(u32, Chord)> {
+    chords_from_midi_events_with_tolerance(events, DEFAULT_MIDI_TIMING_TOLERANCE)
+}
+
+/// Gets the timestamped chords from a sequence of timed (ticks, message) MIDI events.
+///
+/// The events are grouped into slices of simultaneous events (i.e., events within `tolerance` ticks of the first event in
+/// the slice).  Notes that are held across slices remain part of the sounding chord, and a chord is reported (at the
+/// start of its slice) whenever the sounding chord changes.  Slices with fewer than three distinct pitches produce no chord.
+pub fn chords_from_midi_events_with_tolerance(events: &[(u32, MidiMessage)], tolerance: u32) -> Vec<(u32, Chord)> {
+    let mut events = events.to_vec();
+    events.sort_by_key(|(time, _)| *time);
+
+    // The number of active note-ons for each (channel, key).
+    let mut active: BTreeMap<(u8, u8), usize> = BTreeMap::new();
+
+    let mut result = Vec::new();
+    let mut current: Option<Chord> = None;
+    let mut k = 0;
+
+    while k < events.len() {
+        let slice_start = events[k].0;
+
+        while k < events.len() && events[k].0 - slice_start <= tolerance {
+            match events[k].1 {
+                MidiMessage::NoteOn { channel, key, velocity } if velocity > 0 => {
+                    *active.entry((channel, key)).or_default() += 1;
+                }
+                MidiMessage::NoteOn { channel, key, .. } | MidiMessage::NoteOff { channel, key, .. } => {
+                    if let Some(count) = active.get_mut(&(channel, key)) {
+                        *count -= 1;
+
+                        if *count == 0 {
+                            active.remove(&(channel, key));
+                        }
+                    }
+                }
+            }
+
+            k += 1;
+        }
+
+        let mut notes = active.keys().filter_map(|(_, key)| Note::from_midi(*key).ok()).collect::<Vec<_>>();
+        notes.sort();
+        notes.dedup();
+
+        let chord = Chord::try_from_notes_with_duplicate_handling(&notes, DuplicateHandling::Collapse)
+            .ok()
+            .and_then(|c| c.into_iter().next());
+
+        if chord != current {
+            if let Some(chord) = &chord {
+                result.push((slice_start, chord.clone()));
+            }
+
+            current = chord;
+        }
+    }
+
+    result
+}
+
+/// Lays out the note events as a piano roll: a grid of (time step × 88 piano keys), where each cell is whether or not that
+/// key is sounding during that time step.
+///
+/// Each time step is `time_resolution` seconds long, and the grid spans from `0` to the end of the last event.  The keys are
+/// MIDI keys 21 (A0) through 108 (C8); notes outside of the piano's range are ignored.
+pub fn to_piano_roll(events: &[NoteEvent], time_resolution: f32) -> Vec<Vec<bool>> {
+    if time_resolution <= 0.0 {
+        return Vec::new();
+    }
+
+    // Guard against floating point error at the step boundaries.
+    let epsilon = 1e-4;
+    let num_steps = events.iter().map(|e| (e.end() / time_resolution - epsilon).ceil().max(0.0) as usize).max().unwrap_or(0);
+
+    let mut result = vec![vec![false; PIANO_NUM_KEYS]; num_steps];
+
+    for event in events {
+        let Some(key) = (event.note.to_midi() as usize).checked_sub(PIANO_LOWEST_MIDI_KEY as usize).filter(|k| *k < PIANO_NUM_KEYS) else {
+            continue;
+        };
+
+        let first = (event.start.max(0.0) / time_resolution + epsilon).floor() as usize;
+        let last = (event.end() / time_resolution - epsilon).ceil().max(0.0) as usize;
+
+        for step in result.iter_mut().take(last).skip(first) {
+            step[key] = true;
+        }
+    }
+
+    result
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{base::HasName, note::*};
+    use pretty_assertions::assert_eq;
+
+    fn on(key: u8) -> MidiMessage {
+        MidiMessage::NoteOn { channel: 0, key, velocity: 100 }
+    }
+
+    fn off(key: u8) -> MidiMessage {
+        MidiMessage::NoteOff { channel: 0, key, velocity: 0 }
+    }
+
+    #[test]
+    fn test_piano_roll() {
+        // A4 (MIDI key 69) held from 0.5 to 1.5 seconds.
+        let roll = to_piano_roll(&[NoteEvent::new(A, 0.5, 1.0)], 0.1);
+
+        assert_eq!(roll.len(), 15);
+
+        for (step, keys) in roll.iter().enumerate() {
+            for (key, is_active) in keys.iter().enumerate() {
+                assert_eq!(*is_active, key == 69 - 21 && step >= 5, "step {step}, key {key}");
+            }
+        }
+
+        // The extremes of the keyboard are the first and last keys, and notes off the keyboard are ignored.
+        let roll = to_piano_roll(&[NoteEvent::new(AZero, 0.0, 0.1), NoteEvent::new(CEight, 0.0, 0.1), NoteEvent::new(CZero, 0.0, 0.1)], 0.1);
+
+        assert_eq!(roll.len(), 1);
+        assert_eq!(roll[0].iter().filter(|k| **k).count(), 2);
+        assert!(roll[0][0] && roll[0][87]);
+
+        assert_eq!(to_piano_roll(&[], 0.1), Vec::<Vec<bool>>::new());
+    }
+
+    #[test]
+    fn test_chords_from_midi_events() {
+        let events = [
+            // A slightly "rolled" C major.
+            (0, on(60)),
+            (3, on(64)),
+            (6, on(67)),
+            // The C major is held, and a B♭ is added.
+            (480, on(70)),
+            // The C is held into an F major, with the bass below it.
+            (960, off(64)),
+            (960, off(67)),
+            (962, off(70)),
+            (963, on(53)),
+            (965, on(57)),
+            // Silence.
+            (1440, off(53)),
+            (1440, off(57)),
+            (1440, off(60)),
+            // A G major, where a velocity of zero ends the notes.
+            (1920, on(55)),
+            (1921, on(59)),
+            (1925, on(62)),
+            (2400, MidiMessage::NoteOn { channel: 0, key: 55, velocity: 0 }),
+        ];
+
+        let chords = chords_from_midi_events(&events).into_iter().map(|(t, c)| (t, c.name())).collect::<Vec<_>>();
+
+        assert_eq!(chords, vec![(0, "C".to_string()), (480, "C7".to_string()), (960, "F".to_string()), (1920, "G".to_string())]);
+    }
+}
@@ -18,6 +18,13 @@ pub trait HasIsDominant {
     fn is_dominant(&self) -> bool;
 }
 
+/// A trait for types that may be an added tone.
+pub trait HasIsAddedTone {
+    /// Returns whether the type (usually the extension enum) is an added tone (e.g., `add9`), rather than
+    /// an alteration or suspension.
+    fn is_added_tone(&self) -> bool;
+}
+
 // Enum.
 
 /// An enum representing the degree of a dominant chord.
@@ -117,6 +124,12 @@ impl HasIsDominant for Modifier {
     }
 }
 
+impl HasIsAddedTone for Extension {
+    fn is_added_tone(&self) -> bool {
+        matches!(self, Extension::Add2 | Extension::Add4 | Extension::Add6 | Extension::Add9 | Extension::Add11 | Extension::Add13)
+    }
+}
+
 impl HasStaticName for Degree {
     #[coverage(off)]
     fn static_name(&self) -> &'static str {
@@ -254,7 +267,7 @@ static ONE_OFF_MODIFIER_SETS: LazyLock<[Vec<Modifier>; 7]> = LazyLock::new(|| {
     ]
 });
 
-static LIKELY_EXTENSION_SETS: LazyLock<[Vec<Extension>; 11]> = LazyLock::new(|| {
+static LIKELY_EXTENSION_SETS: LazyLock<[Vec<Extension>; 13]> = LazyLock::new(|| {
     [
         vec![],
         vec![Extension::Sus2],
@@ -267,5 +280,8 @@ static LIKELY_EXTENSION_SETS: LazyLock<[Vec<Extension>; 11]> = LazyLock::new(||
         vec![Extension::Add13],
         vec![Extension::Flat11],
         vec![Extension::Sharp13],
+        // Common combinations of added tones (e.g., `C6/9`, and `Cadd9/11`).
+        vec![Extension::Add6, Extension::Add9],
+        vec![Extension::Add9, Extension::Add11],
     ]
 });
@@ -141,8 +141,44 @@ pub enum NamedPitch {
     BTripleSharp,
 }
 
+/// An enharmonic spelling preference for rendering names (e.g., whether the pitch between C and D is `C♯` or `D♭`).
+#[derive(PartialEq, Eq, Copy, Clone, Hash, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Spelling {
+    /// Spell accidentals as sharps (e.g., `C♯`, `F♯`, and `A♯`).
+    Sharps,
+    /// Spell accidentals as flats (e.g., `D♭`, `G♭`, and `B♭`).
+    Flats,
+    /// Spell accidentals by their position on the circle of fifths (i.e., the spelling whose key has the fewest
+    /// accidentals, from `D♭` to `F♯`).
+    #[default]
+    Auto,
+}
+
 // Impls.
 
+impl NamedPitch {
+    /// Returns the enharmonic equivalent of this named pitch with the given spelling, without double (or triple) accidentals.
+    ///
+    /// Naturals are kept (e.g., `E` is never spelled `F♭`), and [`Spelling::Auto`] spells the pitches from `D♭` to `F♯` on the
+    /// circle of fifths.
+    pub fn respell(&self, spelling: Spelling) -> NamedPitch {
+        match spelling {
+            Spelling::Sharps => self.respell_from(NamedPitch::F),
+            Spelling::Flats => self.respell_from(NamedPitch::GFlat),
+            Spelling::Auto => self.respell_from(NamedPitch::DFlat),
+        }
+    }
+
+    /// Returns the enharmonic equivalent of this named pitch among the twelve named pitches that follow `lowest` (inclusive)
+    /// on the line of fifths.
+    pub(crate) fn respell_from(&self, lowest: NamedPitch) -> NamedPitch {
+        let offset = (*self as i8 - lowest as i8).rem_euclid(12);
+
+        lowest + offset
+    }
+}
+
 impl HasNamedPitch for NamedPitch {
     fn named_pitch(&self) -> NamedPitch {
         *self
@@ -485,4 +521,19 @@ mod tests {
         assert_eq!(NamedPitch::from(Pitch::B), NamedPitch::B);
         assert_eq!(NamedPitch::from(&Pitch::B), NamedPitch::B);
     }
+
+    #[test]
+    fn test_respell() {
+        assert_eq!(NamedPitch::DFlat.respell(Spelling::Sharps), NamedPitch::CSharp);
+        assert_eq!(NamedPitch::CSharp.respell(Spelling::Flats), NamedPitch::DFlat);
+        assert_eq!(NamedPitch::ASharp.respell(Spelling::Auto), NamedPitch::BFlat);
+        assert_eq!(NamedPitch::GFlat.respell(Spelling::Auto), NamedPitch::FSharp);
+
+        // Naturals stay natural, and double accidentals are simplified.
+        assert_eq!(NamedPitch::E.respell(Spelling::Flats), NamedPitch::E);
+        assert_eq!(NamedPitch::ESharp.respell(Spelling::Sharps), NamedPitch::F);
+        assert_eq!(NamedPitch::CFlat.respell(Spelling::Flats), NamedPitch::B);
+        assert_eq!(NamedPitch::BDoubleFlat.respell(Spelling::Sharps), NamedPitch::A);
+        assert_eq!(NamedPitch::FDoubleSharp.respell(Spelling::Flats), NamedPitch::G);
+    }
 }
@@ -12,13 +12,13 @@ use std::{
 };
 
 use crate::core::{
-    base::{HasName, HasStaticName, Parsable, Res},
+    base::{HasName, HasSpelledName, HasStaticName, KordError, Parsable, Res},
     chord::Chord,
-    interval::{HasEnharmonicDistance, Interval, PRIMARY_HARMONIC_SERIES},
-    named_pitch::{HasNamedPitch, NamedPitch},
+    interval::{HasEnharmonicDistance, Interval, SpelledInterval, PRIMARY_HARMONIC_SERIES},
+    named_pitch::{HasNamedPitch, NamedPitch, Spelling},
     octave::{HasOctave, Octave, ALL_OCTAVES},
     parser::{note_str_to_note, octave_str_to_octave, ChordParser, Rule},
-    pitch::{HasBaseFrequency, HasFrequency, HasPitch, Pitch, ALL_PITCHES},
+    pitch::{tuning, HasFrequency, HasPitch, Pitch, TuningContext, ALL_PITCHES, SEMITONES_FROM_C0_TO_A4},
 };
 use paste::paste;
 use pest::Parser;
@@ -187,6 +187,118 @@ impl Note {
     pub fn new(pitch: NamedPitch, octave: Octave) -> Self {
         Self { named_pitch: pitch, octave }
     }
+
+    /// Creates a new [`Note`] from the given [`Pitch`] (pitch class) and octave number (e.g., `Note::from_pitch(Pitch::A, 4)` is A4).
+    ///
+    /// Since a [`Pitch`] has no enharmonic spelling, accidentals are spelled as flats.  Panics if the octave is not within `0..=15`.
+    pub fn from_pitch(pitch: Pitch, octave: i8) -> Self {
+        Self::new(pitch.into(), Octave::Zero + octave)
+    }
+
+    /// Creates a new [`Note`] from the given MIDI note number, where A4 is `69` (and C4, or "middle C", is `60`), matching the
+    /// octaves of [`HasFrequency::frequency`].
+    ///
+    /// Accidentals are spelled as flats (see [`Note::from_pitch`]).  Returns an error if the number is not a MIDI note
+    /// number (i.e., not within `0..=127`), or is below C0 (i.e., `12`), which is the lowest representable note.
+    pub fn from_midi(midi: u8) -> Res<Self> {
+        if midi > 127 {
+            return Err(anyhow::Error::msg(format!("MIDI note numbers must be within 0..=127 (got {midi}).")));
+        }
+
+        if midi < 12 {
+            return Err(anyhow::Error::msg(format!("MIDI note number {midi} is below C0, the lowest representable note.")));
+        }
+
+        Ok(Self::from_pitch(Pitch::try_from(midi % 12).unwrap(), (midi / 12) as i8 - 1))
+    }
+
+    /// Returns the MIDI note number of this note, where A4 is `69` (and C4, or "middle C", is `60`).
+    ///
+    /// Enharmonic spellings agree (e.g., B♯3 and C4 are both `60`), and notes above G9 (the highest MIDI note) saturate at `127`.
+    pub fn to_midi(&self) -> u8 {
+        let (_, semitones) = self.diatonic_position();
+
+        (semitones + 12).clamp(0, 127) as u8
+    }
+
+    /// Returns the root of the relative minor key of the major key rooted on this note (i.e., down a minor third).
+    pub fn relative_minor(&self) -> Note {
+        *self - Interval::MinorThird
+    }
+
+    /// Returns the root of the relative major key of the minor key rooted on this note (i.e., up a minor third).
+    pub fn relative_major(&self) -> Note {
+        *self + Interval::MinorThird
+    }
+
+    /// Returns the spelled interval from this note to the other note, counting octaves (e.g., C4 to E5 is a major tenth).
+    ///
+    /// The interval is spelled from the named pitches, so enharmonic notes give different intervals (e.g., C to D♯ is an
+    /// augmented second, while C to E♭ is a minor third).  The interval is descending if the other note is lower (see
+    /// [`SpelledInterval::is_descending`]).
+    pub fn interval_to(&self, other: &Note) -> SpelledInterval {
+        let (from_steps, from_semitones) = self.diatonic_position();
+        let (to_steps, to_semitones) = other.diatonic_position();
+
+        SpelledInterval::from_distance(to_steps - from_steps, to_semitones - from_semitones)
+    }
+
+    /// Returns the enharmonic equivalent of this note with the given spelling (see [`NamedPitch::respell`]), in unison with it
+    /// (e.g., C4 spelled with flats is C4, while C♭4 is B3).
+    pub fn respell(&self, spelling: Spelling) -> Note {
+        self.respell_as(self.named_pitch.respell(spelling))
+    }
+
+    /// Returns the note with the given (enharmonic) named pitch that is in unison with this note.
+    pub(crate) fn respell_as(&self, named_pitch: NamedPitch) -> Note {
+        let (_, semitones) = self.diatonic_position();
+        let (_, respelled_semitones) = Note::new(named_pitch, self.octave).diatonic_position();
+
+        let octave = self.octave as i16 + (semitones - respelled_semitones).div_euclid(12);
+
+        // A note at the edge of the octaves keeps its spelling.
+        match u8::try_from(octave).ok().and_then(|o| Octave::try_from(o).ok()) {
+            Some(octave) => Note::new(named_pitch, octave),
+            None => *self,
+        }
+    }
+
+    /// Returns the position of this note in diatonic steps (i.e., letter names), and in semitones, above C0.
+    ///
+    /// Both are based on the written octave, so B♯3 is a step below C4, but in unison with it.
+    fn diatonic_position(&self) -> (i16, i16) {
+        // The named pitches are ordered by fifths (from F♭♭♭ to B♯♯♯), so each group of seven shares an accidental.
+        let index = self.named_pitch as i16;
+        let step = [3, 0, 4, 1, 5, 2, 6][(index % 7) as usize];
+        let accidental = index / 7 - 3;
+
+        let octave = self.octave as i16;
+
+        (7 * octave + step, 12 * octave + [0, 2, 4, 5, 7, 9, 11][step as usize] + accidental)
+    }
+
+    /// Returns this note, transposed by the given number of semitones (negative values transpose down).
+    ///
+    /// The octave follows the transposition (so the frequency does, too).  The spelling follows the key, as if the interval
+    /// were spelled in its simplest form (e.g., F♯ up a fifth is C♯, while B♭ up a fourth is E♭), and is kept when
+    /// transposing by octaves; a spelling that would need a double accidental is spelled with a single one, instead (see
+    /// [`NamedPitch::respell`]).  A note that would leave the representable octaves is kept within them (by octaves).
+    pub fn transpose(&self, semitones: i8) -> Note {
+        let (_, position) = self.diatonic_position();
+        let mut index = position + semitones as i16;
+
+        while index < 0 {
+            index += 12;
+        }
+
+        while index >= 12 * 16 {
+            index -= 12;
+        }
+
+        let transposed = Note::from_pitch(Pitch::try_from((index % 12) as u8).unwrap(), (index / 12) as i8);
+
+        transposed.respell_as(transposed_named_pitch(self.named_pitch, transposed.named_pitch(), semitones))
+    }
 }
 
 impl Note {
@@ -210,6 +322,15 @@ impl Note {
         get_notes_from_audio_data(data, length_in_seconds)
     }
 
+    /// Attempts to use the provided to identify the notes in the audio data, along with the confidence of each detection
+    /// (see [`get_notes_with_confidence_from_audio_data`](crate::analyze::base::get_notes_with_confidence_from_audio_data)).
+    #[cfg(feature = "analyze_base")]
+    pub fn try_from_audio_with_confidence(data: &[f32], length_in_seconds: f32) -> Res<Vec<(Note, f32)>> {
+        use crate::analyze::base::get_notes_with_confidence_from_audio_data;
+
+        get_notes_with_confidence_from_audio_data(data, length_in_seconds)
+    }
+
     /// Attempts to use the default microphone to listen to audio for the specified time
     /// to identify the notes in the recorded audio using ML.
     ///
@@ -263,10 +384,19 @@ impl HasName for Note {
     }
 }
 
+impl HasSpelledName for Note {
+    fn name_with_spelling(&self, spelling: Spelling) -> String {
+        self.respell(spelling).name()
+    }
+}
+
 impl HasFrequency for Note {
     fn frequency(&self) -> f32 {
+        self.frequency_with_tuning(&tuning())
+    }
+
+    fn frequency_with_tuning(&self, tuning: &TuningContext) -> f32 {
         let mut octave = self.octave();
-        let base_frequency = self.pitch().base_frequency();
 
         match self.named_pitch {
             NamedPitch::ATripleSharp | NamedPitch::BTripleSharp | NamedPitch::BDoubleSharp | NamedPitch::BSharp => {
@@ -278,7 +408,7 @@ impl HasFrequency for Note {
             _ => {}
         }
 
-        base_frequency * 2.0_f32.powf(octave as u8 as f32)
+        tuning.frequency(octave as i32 * 12 + self.pitch() as i32 - SEMITONES_FROM_C0_TO_A4)
     }
 }
 
@@ -293,7 +423,7 @@ impl Parsable for Note {
     where
         Self: Sized,
     {
-        let root = ChordParser::parse(Rule::note_with_octave, input)?.next().unwrap();
+        let root = ChordParser::parse(Rule::note_with_octave, input).map_err(|e| KordError::ParseError(e.to_string()))?.next().unwrap();
 
         assert_eq!(Rule::note_with_octave, root.as_rule());
 
@@ -650,17 +780,47 @@ pub static ALL_PITCH_NOTES: LazyLock<[Note; 192]> = LazyLock::new(|| {
     all_notes.try_into().unwrap()
 });
 
-/// All the notes in all octaves with their frequency.
+/// All the notes in all octaves with their frequency, in the standard tuning (i.e., scale the frequencies by the current
+/// [`tuning`]'s [`TuningContext::ratio`] before comparing them against detected frequencies).
 pub static ALL_PITCH_NOTES_WITH_FREQUENCY: LazyLock<[(Note, f32); 192]> = LazyLock::new(|| {
     let mut all_notes = Vec::with_capacity(132);
 
     for note in ALL_PITCH_NOTES.iter() {
-        all_notes.push((*note, note.frequency()));
+        all_notes.push((*note, note.frequency_with_tuning(&TuningContext::default())));
     }
 
     all_notes.try_into().unwrap()
 });
 
+// Helpers.
+
+/// Returns the named pitch of `from`, transposed by the given number of semitones (whose pitch is that of `transposed`), by
+/// moving it along the line of fifths (e.g., up a minor second is five fifths down, and up a major second is two fifths up).
+///
+/// A tritone moves toward the naturals (so F♯ up a tritone is C, rather than B♯), and a named pitch that would need a double
+/// accidental is respelled with a single one, in the direction of the transposition.
+fn transposed_named_pitch(from: NamedPitch, transposed: NamedPitch, semitones: i8) -> NamedPitch {
+    let fifths = match (7 * semitones as i16).rem_euclid(12) {
+        6 if (from as i16) > NamedPitch::D as i16 => -6,
+        k if k > 6 => k - 12,
+        k => k,
+    };
+
+    if fifths == 0 {
+        return from;
+    }
+
+    let position = from as i16 + fifths;
+
+    if (NamedPitch::FFlat as i16..=NamedPitch::BSharp as i16).contains(&position) {
+        from + fifths as i8
+    } else if fifths > 0 {
+        transposed.respell(Spelling::Sharps)
+    } else {
+        transposed.respell(Spelling::Flats)
+    }
+}
+
 // Tests.
 
 #[cfg(test)]
@@ -832,4 +992,144 @@ mod tests {
     fn test_universal() {
         assert_eq!(FSharpFive.to_universal(), Note::parse("Gb5").unwrap());
     }
+
+    #[test]
+    fn test_from_pitch() {
+        assert_eq!(Note::from_pitch(Pitch::A, 4), A);
+        assert_eq!(Note::from_pitch(Pitch::A, 4).frequency(), 440.0);
+        assert_eq!(Note::from_pitch(Pitch::C, 4), C);
+        assert_eq!(Note::from_pitch(Pitch::DFlat, 2), DFlatTwo);
+        assert_eq!(Note::from_pitch(Pitch::G, 0), GZero);
+        assert_eq!(Note::from_pitch(Pitch::EFlat, 5).frequency(), DSharpFive.frequency());
+    }
+
+    #[test]
+    #[should_panic(expected = "Octave underflow.")]
+    fn test_from_pitch_underflow() {
+        Note::from_pitch(Pitch::C, -1);
+    }
+
+    #[test]
+    fn test_relative_keys() {
+        assert_eq!(C.relative_minor(), AThree);
+        assert_eq!(A.relative_major(), CFive);
+        assert_eq!(EFlat.relative_minor(), C);
+        assert_eq!(FSharp.relative_minor(), DSharp);
+        assert_eq!(C.relative_minor().relative_major(), C);
+    }
+
+    #[test]
+    fn test_respell() {
+        assert_eq!(CSharp.respell(Spelling::Flats), DFlat);
+        assert_eq!(DFlat.respell(Spelling::Sharps), CSharp);
+        assert_eq!(ASharp.respell(Spelling::Auto), BFlat);
+        assert_eq!(E.respell(Spelling::Flats), E);
+
+        // The octave follows the letter, so the note stays in unison.
+        assert_eq!(BSharpThree.respell(Spelling::Flats), C);
+        assert_eq!(CFlatFive.respell(Spelling::Sharps), B);
+
+        assert_eq!(CSharp.name_with_spelling(Spelling::Flats), "D♭4");
+        assert_eq!(GFlatTwo.name_with_spelling(Spelling::Sharps), "F♯2");
+    }
+
+    #[test]
+    fn test_midi() {
+        assert_eq!(Note::from_midi(60).unwrap(), C);
+        assert_eq!(Note::from_midi(69).unwrap(), A);
+        assert_eq!(Note::from_midi(45).unwrap(), ATwo);
+        assert_eq!(Note::from_midi(61).unwrap(), DFlat);
+        assert_eq!(Note::from_midi(127).unwrap(), GNine);
+        assert!(Note::from_midi(11).is_err());
+        assert!(Note::from_midi(128).is_err());
+
+        assert_eq!(C.to_midi(), 60);
+        assert_eq!(A.to_midi(), 69);
+        assert_eq!(CSharp.to_midi(), 61);
+        assert_eq!(BSharpThree.to_midi(), 60);
+        assert_eq!(CFlatFive.to_midi(), 71);
+        assert_eq!(CZero.to_midi(), 12);
+        assert_eq!(ATen.to_midi(), 127);
+
+        // The conversion round-trips, and agrees with the frequency.
+        for midi in 12..=127 {
+            let note = Note::from_midi(midi).unwrap();
+
+            assert_eq!(note.to_midi(), midi);
+            assert!((note.frequency() - 440.0 * 2.0f32.powf((midi as f32 - 69.0) / 12.0)).abs() / note.frequency() < 0.001);
+        }
+    }
+
+    #[test]
+    fn test_interval_to() {
+        let name = |from: Note, to: Note| from.interval_to(&to).name();
+
+        assert_eq!(name(C, C), "perfect unison");
+        assert_eq!(name(C, EFlat), "minor third");
+        assert_eq!(name(C, G), "perfect fifth");
+        assert_eq!(name(C, B), "major seventh");
+        assert_eq!(name(C, CFive), "perfect octave");
+        assert_eq!(name(C, EFive), "major tenth");
+        assert_eq!(name(C, GSix), "perfect 19th");
+        assert_eq!(name(BThree, F), "diminished fifth");
+        assert_eq!(name(F, B), "augmented fourth");
+        assert_eq!(name(A, CFive), "minor third");
+
+        // The spelling matters.
+        assert_eq!(name(C, DSharp), "augmented second");
+        assert_eq!(name(C, CSharp), "augmented unison");
+        assert_eq!(name(C, DFlat), "minor second");
+        assert_eq!(name(C, CFlatFive), "diminished octave");
+        assert_eq!(name(C, FDoubleSharp), "doubly augmented fourth");
+        assert_eq!(name(BSharpThree, C), "diminished second");
+
+        // Descending intervals have the same name, and negative semitones.
+        let descending = C.interval_to(&AThree);
+
+        assert_eq!(descending.name(), "minor third");
+        assert!(descending.is_descending());
+        assert_eq!(descending.semitones(), -3);
+        assert!(!AThree.interval_to(&C).is_descending());
+
+        // The semitones always match the frequencies.
+        for (from, to) in [(C, EFive), (F, B), (C, FDoubleSharp), (B, F), (C, CFlatFive), (BSharpThree, C)] {
+            let semitones = 12.0 * (to.frequency() / from.frequency()).log2();
+
+            assert_eq!(from.interval_to(&to).semitones(), semitones.round() as i16, "{} to {}", from, to);
+        }
+    }
+
+    #[test]
+    fn test_transpose() {
+        assert_eq!(C.transpose(2), D);
+        assert_eq!(B.transpose(1), CFive);
+        assert_eq!(C.transpose(-1), BThree);
+        assert_eq!(CSharp.transpose(12), CSharpFive);
+
+        // The spelling follows the key (and is kept when transposing by octaves).
+        assert_eq!(FSharp.transpose(0), FSharp);
+        assert_eq!(FSharp.transpose(7), CSharpFive);
+        assert_eq!(BFlat.transpose(5), EFlatFive);
+        assert_eq!(C.transpose(1), DFlat);
+        assert_eq!(C.transpose(6), FSharp);
+        assert_eq!(FSharp.transpose(6), CFive);
+        assert_eq!(BSharpThree.transpose(0), BSharpThree);
+        assert_eq!(BSharpThree.transpose(2), D);
+        assert_eq!(CSharp.transpose(4), ESharp);
+        assert_eq!(GSharp.transpose(4), BSharp);
+        assert_eq!(DFlat.transpose(-24).transpose(24), DFlat);
+        assert_eq!(A.transpose(-24).frequency(), A.frequency() / 4.0);
+
+        // Notes stay within the representable octaves.
+        assert_eq!(CZero.transpose(-1), BZero);
+        assert_eq!(Note::from_pitch(Pitch::B, 15).transpose(1), Note::from_pitch(Pitch::C, 15));
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_serde() {
+        for note in ALL_PITCH_NOTES.iter().chain([&CSharp, &BSharpThree, &FDoubleSharp]) {
+            assert_eq!(serde_json::from_str::<Note>(&serde_json::to_string(note).unwrap()).unwrap(), *note);
+        }
+    }
 }
@@ -4,7 +4,7 @@
 
 use std::time::Duration;
 
-use std::sync::LazyLock;
+use std::{cell::Cell, sync::LazyLock};
 
 use super::helpers::mel;
 
@@ -25,9 +25,15 @@ pub trait HasBaseFrequency {
 
 /// A trait for types that have a frequency property.
 pub trait HasFrequency {
-    /// Returns the frequency of the type (usually a [`Note`]).
+    /// Returns the frequency of the type (usually a [`Note`]), relative to the current thread's [`tuning`].
     fn frequency(&self) -> f32;
 
+    /// Returns the frequency of the type (usually a [`Note`]), relative to the given tuning (rather than the current thread's
+    /// [`tuning`]).
+    fn frequency_with_tuning(&self, tuning: &TuningContext) -> f32 {
+        self.frequency() / self::tuning().ratio() * tuning.ratio()
+    }
+
     /// Returns the frequency range of the type (usually a [`Note`]).
     /// Essentially, mid way between the frequency and the next frequency on either side.
     fn frequency_range(&self) -> (f32, f32) {
@@ -70,12 +76,66 @@ impl<T: HasFrequency> Playable for T {
     }
 }
 
+// Struct.
+
+/// A reference tuning, which all frequencies are derived from (i.e., the frequency of A4).
+///
+/// The default is the [standard](https://en.wikipedia.org/wiki/A4_(pitch_standard)) [`DEFAULT_A4_FREQUENCY`]; orchestras often
+/// tune higher (e.g., `442`), and historical instruments lower (e.g., `415`).
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct TuningContext {
+    /// The frequency (in Hz) of A4.
+    pub a4_hz: f32,
+}
+
+impl TuningContext {
+    /// Creates a new tuning with the given frequency (in Hz) of A4.
+    pub fn new(a4_hz: f32) -> Self {
+        Self { a4_hz }
+    }
+
+    /// Returns the ratio of this tuning to the standard tuning (i.e., the factor that all frequencies are scaled by).
+    pub fn ratio(&self) -> f32 {
+        self.a4_hz / DEFAULT_A4_FREQUENCY
+    }
+
+    /// Returns the (equal-tempered) frequency of the pitch the given number of semitones above A4 (or below it, if negative).
+    pub fn frequency(&self, semitones_from_a4: i32) -> f32 {
+        self.a4_hz * 2.0_f32.powf(semitones_from_a4 as f32 / 12.0)
+    }
+
+    /// Returns the (fractional) number of semitones that the frequency is above A4 (or below it, if negative).
+    pub fn semitones_from_a4(&self, frequency: f32) -> f32 {
+        12.0 * (frequency / self.a4_hz).log2()
+    }
+}
+
+impl Default for TuningContext {
+    fn default() -> Self {
+        Self::new(DEFAULT_A4_FREQUENCY)
+    }
+}
+
+/// Restores the previous tuning when dropped (see [`with_tuning`]).
+struct TuningGuard(TuningContext);
+
+impl Drop for TuningGuard {
+    fn drop(&mut self) {
+        set_tuning(self.0);
+    }
+}
+
 // Enum.
 
 /// An enum representing the pitch of a note.
 ///
-/// The frequencies of the pitches are based on the [A4 frequency](https://en.wikipedia.org/wiki/A4_(pitch_standard)).
+/// The (base) frequencies of the pitches are those of octave 0, derived (in equal temperament) from the
+/// [standard A4 frequency](https://en.wikipedia.org/wiki/A4_(pitch_standard)), and every note's frequency is derived from the
+/// A4 of its tuning, in the same way (see [`TuningContext`]).
 /// There is no enharmonic representation here, so all of the sharps are represented.
+///
+/// With the `serde` feature, a pitch is serialized as the name of its variant (e.g., `"DFlat"`), rather than its number.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(PartialEq, Eq, Clone, Copy, Hash, Debug, Ord, PartialOrd)]
 #[repr(u8)]
@@ -108,23 +168,17 @@ pub enum Pitch {
 
 // Pitch impls.
 
+impl Pitch {
+    /// Returns this pitch, transposed by the given number of semitones (negative values transpose down), wrapping around
+    /// within the 12 pitches.
+    pub fn transpose(&self, semitones: i8) -> Pitch {
+        Pitch::try_from((*self as i16 + semitones as i16).rem_euclid(12) as u8).unwrap()
+    }
+}
+
 impl HasBaseFrequency for Pitch {
-    #[coverage(off)]
     fn base_frequency(&self) -> f32 {
-        match self {
-            Pitch::C => 16.35,
-            Pitch::DFlat => 17.32,
-            Pitch::D => 18.35,
-            Pitch::EFlat => 19.45,
-            Pitch::E => 20.60,
-            Pitch::F => 21.83,
-            Pitch::GFlat => 23.12,
-            Pitch::G => 24.50,
-            Pitch::AFlat => 25.96,
-            Pitch::A => 27.50,
-            Pitch::BFlat => 29.14,
-            Pitch::B => 30.87,
-        }
+        TuningContext::default().frequency(*self as i32 - SEMITONES_FROM_C0_TO_A4)
     }
 }
 
@@ -158,6 +212,17 @@ impl TryFrom<u8> for Pitch {
 
 // Statics.
 
+/// The standard frequency (in Hz) of A4, which the base frequencies of the pitches are based on.
+pub static DEFAULT_A4_FREQUENCY: f32 = 440.0;
+
+/// The number of semitones from C0 up to A4.
+pub static SEMITONES_FROM_C0_TO_A4: i32 = 57;
+
+thread_local! {
+    /// The tuning of the current thread.
+    static TUNING: Cell<TuningContext> = Cell::new(TuningContext::default());
+}
+
 /// An array of all the pitches.
 pub static ALL_PITCHES: LazyLock<[Pitch; 12]> = LazyLock::new(|| {
     [
@@ -176,6 +241,26 @@ pub static ALL_PITCHES: LazyLock<[Pitch; 12]> = LazyLock::new(|| {
     ]
 });
 
+// Functions.
+
+/// Returns the tuning that frequencies (e.g., [`HasFrequency::frequency`], and audio detection) are relative to on the current thread.
+pub fn tuning() -> TuningContext {
+    TUNING.with(|t| t.get())
+}
+
+/// Sets the tuning that frequencies (e.g., [`HasFrequency::frequency`], and audio detection) are relative to on the current thread.
+pub fn set_tuning(tuning: TuningContext) {
+    TUNING.with(|t| t.set(tuning));
+}
+
+/// Runs the closure with the given tuning on the current thread, and then restores the previous tuning.
+pub fn with_tuning<T>(tuning: TuningContext, f: impl FnOnce() -> T) -> T {
+    let _guard = TuningGuard(self::tuning());
+    set_tuning(tuning);
+
+    f()
+}
+
 // Tests.
 
 #[cfg(test)]
@@ -186,6 +271,51 @@ mod tests {
     #[test]
     fn test_properties() {
         assert_eq!(Pitch::G.pitch(), Pitch::G);
-        assert_eq!(Pitch::G.base_frequency(), 24.50);
+        assert_eq!(format!("{:.2}", Pitch::G.base_frequency()), "24.50");
+        assert_eq!(format!("{:.2}", Pitch::C.base_frequency()), "16.35");
+        assert_eq!(Pitch::A.base_frequency(), 27.5);
+    }
+
+    #[test]
+    fn test_tuning() {
+        assert_eq!(tuning(), TuningContext::new(440.0));
+        assert_eq!(TuningContext::new(415.0).ratio(), 415.0 / 440.0);
+
+        // Frequencies are derived from A4.
+        assert_eq!(TuningContext::new(442.0).frequency(0), 442.0);
+        assert_eq!(TuningContext::new(442.0).frequency(12), 884.0);
+        assert_eq!(TuningContext::new(415.0).frequency(-24), 103.75);
+        assert_eq!(format!("{:.2}", TuningContext::new(415.0).frequency(3)), "493.52");
+        assert_eq!(TuningContext::new(415.0).semitones_from_a4(830.0), 12.0);
+
+        with_tuning(TuningContext::new(442.0), || {
+            assert_eq!(tuning().a4_hz, 442.0);
+
+            with_tuning(TuningContext::new(415.0), || assert_eq!(tuning().a4_hz, 415.0));
+
+            assert_eq!(tuning().a4_hz, 442.0);
+        });
+
+        assert_eq!(tuning(), TuningContext::default());
+    }
+
+    #[test]
+    fn test_transpose() {
+        assert_eq!(Pitch::C.transpose(2), Pitch::D);
+        assert_eq!(Pitch::B.transpose(1), Pitch::C);
+        assert_eq!(Pitch::C.transpose(-1), Pitch::B);
+        assert_eq!(Pitch::A.transpose(-25), Pitch::AFlat);
+        assert_eq!(Pitch::G.transpose(0), Pitch::G);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_serde() {
+        assert_eq!(serde_json::to_string(&Pitch::DFlat).unwrap(), "\"DFlat\"");
+        assert_eq!(serde_json::from_str::<Pitch>("\"C\"").unwrap(), Pitch::C);
+
+        for pitch in *ALL_PITCHES {
+            assert_eq!(serde_json::from_str::<Pitch>(&serde_json::to_string(&pitch).unwrap()).unwrap(), pitch);
+        }
     }
 }
@@ -0,0 +1,540 @@
+//! A module for working with chord progressions.
+
+use crate::core::{
+    base::{HasName, KordError, Res},
+    chord::{Chord, Chordable, HasChord, HasKnownChord, HasModifiers, HasRoot},
+    interval::Interval,
+    known_chord::{HasRelativeChord, KnownChord},
+    modifier::{Degree, Modifier},
+    named_pitch::HasNamedPitch,
+    note::Note,
+    octave::Octave,
+    pitch::{HasPitch, Pitch},
+    scale::{Scale, ScaleKind},
+};
+
+// Statics.
+
+/// The number of beats in a bar (i.e., lead sheets are laid out in 4/4).
+pub static BEATS_PER_BAR: usize = 4;
+
+/// The roman numerals of the scale degrees.
+static ROMAN_NUMERALS: [&str; 7] = ["I", "II", "III", "IV", "V", "VI", "VII"];
+
+/// The octave that generated bass lines are rooted in.
+pub static BASS_OCTAVE: Octave = Octave::Two;
+
+// Enum.
+
+/// The pattern used to generate a bass line from a chord sequence.
+#[derive(PartialEq, Eq, Copy, Clone, Hash, Debug, Default)]
+pub enum BassPattern {
+    /// The root of each chord, held for the whole chord.
+    #[default]
+    Root,
+    /// The root of each chord, followed by its fifth halfway through the chord.
+    RootFifth,
+    /// A walking line of four notes per chord: the root, the third, the fifth, and a chromatic approach to the next root.
+    Walking,
+}
+
+// Struct.
+
+/// A chord progression in a (major) key, e.g., `ii V I` in C.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Progression {
+    key: Pitch,
+    chords: Vec<Chord>,
+}
+
+// Impls.
+
+impl Progression {
+    /// Parses a progression of roman numerals (separated by spaces, `-`, `|`, or `,`) into concrete chords in the major key on
+    /// the given pitch (e.g., `ii V7 I` in C is Dm, G7, and C).
+    ///
+    /// Each numeral may be prefixed by an accidental (`♭`, `b`, `♯`, or `#`) for chromatic degrees, and is followed by an optional
+    /// quality mark (`°` or `o` for diminished, `ø` for half diminished, `+` for augmented), and an optional figure (`6` and `64`
+    /// for triad inversions; `7`, `65`, `43`, and `42` for sevenths; `9`, `11`, `13`, or `maj7`).  Uppercase numerals are major,
+    /// and lowercase numerals are minor.  A seventh is diatonic on a major chord whose root and seventh are in the key (e.g.,
+    /// `I7` is Cmaj7 in C), and is otherwise minor (e.g., `V7` is G7).
+    ///
+    /// Secondary functions are written as `x/y` (e.g., `V/V` is D in C), where `x` is read in the major key on the root of `y`.
+    pub fn parse(key: Pitch, input: &str) -> Res<Progression> {
+        let scale = major_key(key);
+
+        let chords = input
+            .split(|c: char| c.is_whitespace() || matches!(c, '-' | '|' | ','))
+            .filter(|t| !t.is_empty())
+            .map(|t| parse_numeral(t, &scale))
+            .collect::<Res<Vec<_>>>()?;
+
+        Ok(Progression { key, chords })
+    }
+
+    /// Returns the roman numeral of each of the given chords in the major key on the given pitch (see [`functional_label`]).
+    pub fn analyze(key: Pitch, chords: &[Chord]) -> Vec<String> {
+        let scale = major_key(key);
+
+        chords.iter().map(|c| functional_label(c, &scale)).collect()
+    }
+
+    /// Returns the key (i.e., the tonic of the major key) of the progression.
+    pub fn key(&self) -> Pitch {
+        self.key
+    }
+
+    /// Returns the chords of the progression.
+    pub fn chords(&self) -> &[Chord] {
+        &self.chords
+    }
+
+    /// Returns the roman numerals of the chords of the progression (see [`Progression::analyze`]).
+    pub fn numerals(&self) -> Vec<String> {
+        Progression::analyze(self.key, &self.chords)
+    }
+}
+
+// Functions.
+
+/// Lays out a sequence of timed chords (start time in seconds, chord) as a plain text lead sheet.
+///
+/// The tempo is used to group the chords into bars of [`BEATS_PER_BAR`] beats, e.g., `| C G | Am F |`.  A bar in which no
+/// new chord starts is written as `%` (i.e., the previous chord keeps ringing).
+pub fn to_lead_sheet(chords: &[(f32, Chord)], bpm: f32) -> String {
+    if chords.is_empty() || bpm <= 0.0 {
+        return String::new();
+    }
+
+    let seconds_per_bar = 60.0 / bpm * BEATS_PER_BAR as f32;
+    let bar_of = |time: f32| (time.max(0.0) / seconds_per_bar + 1e-4).floor() as usize;
+
+    let num_bars = chords.iter().map(|(t, _)| bar_of(*t)).max().unwrap_or(0) + 1;
+    let mut bars = vec![Vec::new(); num_bars];
+
+    for (time, chord) in chords {
+        bars[bar_of(*time)].push(chord.name());
+    }
+
+    let mut result = String::from("|");
+
+    for bar in bars {
+        let content = if bar.is_empty() { "%".to_string() } else { bar.join(" ") };
+
+        result.push_str(&format!(" {content} |"));
+    }
+
+    result
+}
+
+/// Returns the roman numeral of the diatonic triad built on the given (1-based) degree of the key (e.g., `ii` for D in C major).
+///
+/// Major (and augmented) triads are uppercase, minor (and diminished) triads are lowercase, and diminished triads are marked with `°`.
+pub fn degree_numeral(key: &Scale, degree: u8) -> Option<String> {
+    let triad = key.diatonic_chords().into_iter().nth(degree.checked_sub(1)? as usize)?;
+    let numeral = ROMAN_NUMERALS[degree as usize - 1];
+
+    let result = match triad.known_chord() {
+        KnownChord::Minor => numeral.to_lowercase(),
+        KnownChord::Diminished => format!("{}°", numeral.to_lowercase()),
+        KnownChord::Augmented => format!("{numeral}+"),
+        _ => numeral.to_string(),
+    };
+
+    Some(result)
+}
+
+/// Returns the roman numeral of the given chord in the key (see [`functional_label`]), if the chord is diatonic to the key
+/// (i.e., all of its tones are in the scale).
+pub fn roman_numeral(chord: &Chord, key: &Scale) -> Option<String> {
+    let (label, function) = label_chord(chord, key);
+
+    (function == Function::Diatonic).then_some(label)
+}
+
+/// Returns the secondary dominant label of the given chord in the key (e.g., `V7/V` for D7 in C major, see
+/// [`functional_label`]), if it is one.
+///
+/// A secondary dominant is a non-diatonic major triad (or dominant seventh) whose root is a perfect fifth above a diatonic
+/// degree other than the tonic, where that degree's triad is not diminished.
+pub fn secondary_dominant(chord: &Chord, key: &Scale) -> Option<String> {
+    let (label, function) = label_chord(chord, key);
+
+    (function == Function::SecondaryDominant).then_some(label)
+}
+
+/// Labels each chord of a progression with its function in the key (see [`functional_label`]), if it is diatonic, or a
+/// secondary function (e.g., `V7/V`, or `vii°7/ii`), or `None` otherwise (i.e., for borrowed, and chromatic chords).
+pub fn analyze_progression(chords: &[Chord], key: &Scale) -> Vec<Option<String>> {
+    chords
+        .iter()
+        .map(|c| {
+            let (label, function) = label_chord(c, key);
+
+            (function != Function::Chromatic).then_some(label)
+        })
+        .collect()
+}
+
+/// Returns the complete functional label of the given chord in the key (e.g., `V7/V`, `ii°6`, or `♭VII`).
+///
+/// The numeral is cased by the chord's own third (uppercase for major, lowercase for minor), marked with its quality (`°` for
+/// diminished, `ø` for half diminished, `+` for augmented), and followed by the figured bass of its inversion (`6` and `64`
+/// for triads; `7`, `65`, `43`, and `42` for sevenths, where a root position ninth, eleventh, or thirteenth uses that figure
+/// instead).  Non-diatonic chords are labeled as secondary dominants (`V/x`) or secondary leading-tone chords (`vii°/x`)
+/// when possible, and otherwise as chromatic (`♭` or `♯`) degrees.
+pub fn functional_label(chord: &Chord, key: &Scale) -> String {
+    label_chord(chord, key).0
+}
+
+/// Generates a timestamped bass line from a sequence of timed (start time in seconds, chord) pairs.
+///
+/// Each chord lasts until the next chord starts (the last chord lasts as long as the one before it), and its span is
+/// divided evenly among the notes of the pattern.  The bass line is rooted in [`BASS_OCTAVE`], and the first note of each
+/// chord is its bass note (i.e., the root, unless the chord has a slash or is inverted).
+pub fn generate_bass_line(chords: &[(f32, Chord)], pattern: BassPattern) -> Vec<(f32, Note)> {
+    let mut result = Vec::new();
+
+    for (k, (start, chord)) in chords.iter().enumerate() {
+        let root = Note::new(chord.root().named_pitch(), BASS_OCTAVE);
+        let bass = Note::new(chord.chord()[0].named_pitch(), BASS_OCTAVE);
+
+        let duration = match (chords.get(k + 1), k.checked_sub(1).and_then(|p| chords.get(p))) {
+            (Some((next, _)), _) => next - start,
+            (None, Some((previous, _))) => start - previous,
+            (None, None) => 0.0,
+        };
+
+        let tone = |index: usize| chord.relative_chord().get(index).map(|i| root + *i).unwrap_or(root);
+
+        let notes = match pattern {
+            BassPattern::Root => vec![bass],
+            BassPattern::RootFifth => vec![bass, root + Interval::PerfectFifth],
+            BassPattern::Walking => {
+                let next_root = chords.get(k + 1).map(|(_, c)| Note::new(c.root().named_pitch(), BASS_OCTAVE)).unwrap_or(root);
+
+                vec![bass, tone(1), tone(2), next_root - Interval::MinorSecond]
+            }
+        };
+
+        // Without a duration, only the first note of the pattern can be placed.
+        let notes = if duration > 0.0 { notes } else { notes[..1].to_vec() };
+        let step = duration / notes.len() as f32;
+
+        result.extend(notes.into_iter().enumerate().map(|(j, note)| (start + step * j as f32, note)));
+    }
+
+    result
+}
+
+// Helpers.
+
+/// How a chord relates to a key (see [`label_chord`]).
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+enum Function {
+    /// All of the chord's tones are in the key.
+    Diatonic,
+    /// The chord is the (major, or dominant seventh) dominant of a degree of the key.
+    SecondaryDominant,
+    /// The chord is the (diminished) leading-tone chord of a degree of the key.
+    SecondaryLeadingTone,
+    /// The chord is borrowed, or built on a chromatic degree.
+    Chromatic,
+}
+
+/// Labels the given chord in the key (see [`functional_label`] for the notation), along with how it relates to the key.
+fn label_chord(chord: &Chord, key: &Scale) -> (String, Function) {
+    let root = chord.root();
+    let semitones_from_root = |n: Note| (n.pitch() as u8 + 12 - root.pitch() as u8) % 12;
+
+    let tones = chord.chord().into_iter().map(semitones_from_root).collect::<Vec<_>>();
+    let has = |semitones: u8| tones.contains(&semitones);
+
+    let is_minor = has(3) && !has(4);
+    let is_diminished = is_minor && has(6) && !has(7);
+    let is_augmented = !is_minor && has(8) && !has(7);
+    let seventh = [10, 11].into_iter().find(|s| has(*s)).or_else(|| Some(9).filter(|s| is_diminished && has(*s)));
+
+    // The quality mark.
+
+    let mark = match (is_diminished, seventh) {
+        (true, Some(10)) => "ø",
+        (true, _) => "°",
+        _ if is_augmented => "+",
+        _ => "",
+    };
+
+    // The figured bass, from the chord tone in the bass.
+
+    let bass = semitones_from_root(chord.chord()[0]);
+    let inversion = match bass {
+        3 | 4 => 1,
+        6..=8 => 2,
+        _ if Some(bass) == seventh => 3,
+        _ => 0,
+    };
+
+    let figure = match (seventh, inversion) {
+        (None, 1) => "6",
+        (None, 2) => "64",
+        (None, _) => "",
+        (Some(_), 1) => "65",
+        (Some(_), 2) => "43",
+        (Some(_), 3) => "42",
+        (Some(_), _) => [(Degree::Thirteen, "13"), (Degree::Eleven, "11"), (Degree::Nine, "9")]
+            .into_iter()
+            .find(|(d, _)| chord.modifiers().contains(&Modifier::Dominant(*d)))
+            .map(|(_, f)| f)
+            .unwrap_or("7"),
+    };
+
+    let numeral = |degree: u8| {
+        let numeral = ROMAN_NUMERALS[degree as usize - 1];
+
+        if is_minor {
+            numeral.to_lowercase()
+        } else {
+            numeral.to_string()
+        }
+    };
+
+    // Secondary functions, for chords that are not diatonic.
+
+    let is_diatonic = chord.chord().into_iter().all(|n| key.degree_of(n).is_some());
+    let tonicizable = |target: Note| key.degree_of(target).filter(|d| *d != 1).and_then(|d| degree_numeral(key, d)).filter(|n| !n.ends_with('°'));
+
+    if !is_diatonic {
+        if !is_minor && !is_augmented && matches!(seventh, None | Some(10)) {
+            if let Some(target) = tonicizable(root - Interval::PerfectFifth) {
+                return (format!("V{figure}/{target}"), Function::SecondaryDominant);
+            }
+        }
+
+        if is_diminished {
+            if let Some(target) = tonicizable(root + Interval::MinorSecond) {
+                return (format!("vii{mark}{figure}/{target}"), Function::SecondaryLeadingTone);
+            }
+        }
+    }
+
+    // Diatonic (or borrowed) roots, and chromatic roots.
+
+    let (accidental, degree) = match key.degree_of(root) {
+        Some(degree) => ("", degree),
+        None => match key.degree_of(root + Interval::MinorSecond) {
+            Some(degree) => ("♭", degree),
+            None => ("♯", key.degree_of(root - Interval::MinorSecond).unwrap_or(1)),
+        },
+    };
+
+    let function = if is_diatonic { Function::Diatonic } else { Function::Chromatic };
+
+    (format!("{accidental}{}{mark}{figure}", numeral(degree)), function)
+}
+
+/// Returns the major key on the given pitch.
+fn major_key(pitch: Pitch) -> Scale {
+    Scale::new(Note::from_pitch(pitch, 4), ScaleKind::Major)
+}
+
+/// Parses a single roman numeral (and any secondary targets) into a chord in the given key.
+fn parse_numeral(token: &str, key: &Scale) -> Res<Chord> {
+    let invalid = || KordError::ParseError(format!("Invalid roman numeral: {token}"));
+
+    // Secondary functions are read in the major key on the root of their target.
+
+    let (token, key) = match token.split_once('/') {
+        Some((numeral, target)) => (numeral, Scale::new(parse_numeral(target, key)?.root(), ScaleKind::Major)),
+        None => (token, *key),
+    };
+
+    let (accidental, rest) = match token.chars().next() {
+        Some(accidental @ ('♭' | 'b' | '♯' | '#')) => (Some(accidental), &token[accidental.len_utf8()..]),
+        _ => (None, token),
+    };
+
+    let numeral_length = rest.find(|c: char| !matches!(c, 'I' | 'V' | 'i' | 'v')).unwrap_or(rest.len());
+    let (numeral, rest) = rest.split_at(numeral_length);
+
+    let is_minor = match numeral {
+        _ if numeral.chars().all(|c| c.is_ascii_lowercase()) => true,
+        _ if numeral.chars().all(|c| c.is_ascii_uppercase()) => false,
+        _ => return Err(invalid().into()),
+    };
+
+    let degree = ROMAN_NUMERALS.iter().position(|n| *n == numeral.to_uppercase()).ok_or_else(invalid)?;
+    let root = match accidental {
+        Some('♭' | 'b') => key.notes()[degree] - Interval::AugmentedUnison,
+        Some(_) => key.notes()[degree] + Interval::AugmentedUnison,
+        None => key.notes()[degree],
+    };
+
+    let (mark, figure) = match rest.chars().next() {
+        Some(mark @ ('°' | 'o' | 'ø' | '+')) => (Some(mark), &rest[mark.len_utf8()..]),
+        _ => (None, rest),
+    };
+
+    let (inversion, seventh) = match figure {
+        "" | "9" | "11" | "13" | "maj7" => (0, !figure.is_empty()),
+        "6" => (1, false),
+        "64" => (2, false),
+        "7" => (0, true),
+        "65" => (1, true),
+        "43" => (2, true),
+        "42" => (3, true),
+        _ => return Err(invalid().into()),
+    };
+
+    let chord = Chord::new(root);
+
+    let chord = match (mark, seventh) {
+        (Some('ø'), _) => chord.half_diminished(),
+        (Some('°' | 'o'), true) => chord.diminished(),
+        (Some('°' | 'o'), false) => chord.minor().flat5(),
+        (Some(_), _) => chord.augmented(),
+        _ if is_minor => chord.minor(),
+        _ => chord,
+    };
+
+    let is_diatonic_major_seventh = mark.is_none() && !is_minor && accidental.is_none() && key.degree_of(root + Interval::MajorSeventh).is_some();
+
+    let chord = match figure {
+        "9" => chord.dominant9(),
+        "11" => chord.dominant11(),
+        "13" => chord.dominant13(),
+        "maj7" => chord.major7(),
+        _ if !seventh || mark.is_some_and(|m| m != '+') => chord,
+        _ if is_diatonic_major_seventh => chord.major7(),
+        _ => chord.seven(),
+    };
+
+    Ok(chord.with_inversion(inversion))
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{
+        base::Parsable,
+        note::*,
+        scale::{Scale, ScaleKind},
+    };
+    use pretty_assertions::assert_eq;
+
+    fn timed(chords: &[(f32, &str)]) -> Vec<(f32, Chord)> {
+        chords.iter().map(|(t, c)| (*t, Chord::parse(c).unwrap())).collect()
+    }
+
+    #[test]
+    fn test_lead_sheet() {
+        // One chord per beat at 120 bpm fits in a single bar.
+        assert_eq!(to_lead_sheet(&timed(&[(0.0, "C"), (0.5, "G"), (1.0, "Am"), (1.5, "F")]), 120.0), "| C G Am F |");
+
+        // One chord per bar, with a bar where nothing changes.
+        assert_eq!(to_lead_sheet(&timed(&[(0.0, "Cmaj7"), (2.0, "Dm7"), (6.0, "G7")]), 120.0), "| Cmaj7 | Dm7 | % | G7 |");
+
+        assert_eq!(to_lead_sheet(&[], 120.0), "");
+    }
+
+    #[test]
+    fn test_secondary_dominants() {
+        let key = Scale::new(C, ScaleKind::Major);
+        let chords = |names: &[&str]| names.iter().map(|c| Chord::parse(c).unwrap()).collect::<Vec<_>>();
+
+        assert_eq!(
+            analyze_progression(&chords(&["D7", "G7", "C"]), &key),
+            vec![Some("V7/V".to_string()), Some("V7".to_string()), Some("I".to_string())]
+        );
+        assert_eq!(
+            analyze_progression(&chords(&["A7", "Dm", "E", "Am", "C7", "F", "E♭"]), &key),
+            vec![
+                Some("V7/ii".to_string()),
+                Some("ii".to_string()),
+                Some("V/vi".to_string()),
+                Some("vi".to_string()),
+                Some("V7/IV".to_string()),
+                Some("IV".to_string()),
+                None
+            ]
+        );
+
+        // The target of a secondary dominant cannot be diminished.
+        assert_eq!(secondary_dominant(&Chord::parse("F#7").unwrap(), &key), None);
+
+        // The labels are the functional labels.
+        assert_eq!(secondary_dominant(&Chord::parse("D7/F#").unwrap(), &key), Some("V65/V".to_string()));
+        assert_eq!(roman_numeral(&Chord::parse("Bm7b5").unwrap(), &key), Some("viiø7".to_string()));
+        assert_eq!(roman_numeral(&Chord::parse("D7").unwrap(), &key), None);
+        assert_eq!(roman_numeral(&Chord::parse("Fm").unwrap(), &key), None);
+    }
+
+    #[test]
+    fn test_progression() {
+        let names = |p: &Progression| p.chords().iter().map(|c| c.name()).collect::<Vec<_>>();
+
+        assert_eq!(names(&Progression::parse(Pitch::C, "ii V I").unwrap()), vec!["Dm", "G", "C"]);
+        assert_eq!(names(&Progression::parse(Pitch::C, "ii7 - V7 - I7").unwrap()), vec!["Dm7", "G7", "Cmaj7"]);
+        assert_eq!(names(&Progression::parse(Pitch::G, "I | vi | IV | V").unwrap()), vec!["G", "Em", "C", "D"]);
+
+        // Case decides the quality.
+        assert_eq!(names(&Progression::parse(Pitch::C, "IV iv II ii").unwrap()), vec!["F", "Fm", "D", "Dm"]);
+
+        // Secondary dominants, and chromatic degrees.
+        assert_eq!(names(&Progression::parse(Pitch::C, "V/V V7/ii vii°7/V ♭VII").unwrap()), vec!["D", "A7", "F♯dim", "B♭"]);
+
+        // Inversions.
+        assert_eq!(names(&Progression::parse(Pitch::C, "I6 V43").unwrap()), vec!["C/E", "G7/D"]);
+
+        assert!(Progression::parse(Pitch::C, "ii X I").is_err());
+        assert!(Progression::parse(Pitch::C, "Vi").is_err());
+        assert!(Progression::parse(Pitch::C, "V/Q").is_err());
+
+        // And back to roman numerals.
+        let progression = Progression::parse(Pitch::F, "I vi ii7 V7/V V7 I").unwrap();
+
+        assert_eq!(progression.numerals(), vec!["I", "vi", "ii7", "V7/V", "V7", "I"]);
+        assert_eq!(
+            Progression::analyze(Pitch::C, &["C", "A7", "Dm", "G7", "E♭"].map(|c| Chord::parse(c).unwrap())),
+            vec!["I", "V7/ii", "ii", "V7", "♭III"]
+        );
+    }
+
+    #[test]
+    fn test_analyses_agree() {
+        let chords = ["C", "A7", "C#dim7", "Dm7", "D7/F#", "G7", "G/B", "Bm7b5", "Fm", "E♭", "B♭"].map(|c| Chord::parse(c).unwrap());
+        let key = Scale::new(C, ScaleKind::Major);
+
+        let labels = Progression::analyze(Pitch::C, &chords);
+        let functions = analyze_progression(&chords, &key);
+
+        assert_eq!(labels, chords.iter().map(|c| functional_label(c, &key)).collect::<Vec<_>>());
+        assert_eq!(labels, chords.iter().map(|c| c.relative_to(C, false)).collect::<Vec<_>>());
+
+        // Where the function is labeled, it is labeled the same way; only borrowed, and chromatic chords are left out.
+        for ((label, function), chord) in labels.iter().zip(&functions).zip(&chords) {
+            assert!(function.is_none() || function.as_ref() == Some(label), "{} is {label}, but {function:?}", chord.name());
+        }
+
+        assert_eq!(functions.iter().filter(|f| f.is_none()).count(), 3);
+        assert_eq!(functions[2], Some("vii°7/ii".to_string()));
+    }
+
+    #[test]
+    fn test_bass_line() {
+        let chords = timed(&[(0.0, "C"), (2.0, "Am"), (4.0, "F"), (6.0, "G7")]);
+
+        assert_eq!(generate_bass_line(&chords, BassPattern::Root), vec![(0.0, CTwo), (2.0, ATwo), (4.0, FTwo), (6.0, GTwo)]);
+
+        assert_eq!(generate_bass_line(&chords[..2], BassPattern::RootFifth), vec![(0.0, CTwo), (1.0, GTwo), (2.0, ATwo), (3.0, EThree)]);
+
+        assert_eq!(
+            generate_bass_line(&chords[..2], BassPattern::Walking),
+            vec![(0.0, CTwo), (0.5, ETwo), (1.0, GTwo), (1.5, GSharpTwo), (2.0, ATwo), (2.5, CThree), (3.0, EThree), (3.5, GSharpTwo)]
+        );
+
+        // A slash chord starts on its bass note.
+        assert_eq!(generate_bass_line(&timed(&[(0.0, "C/E")]), BassPattern::Root), vec![(0.0, ETwo)]);
+    }
+}
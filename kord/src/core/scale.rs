@@ -0,0 +1,462 @@
+//! A module for working with scales.
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::core::{
+    base::{HasName, HasStaticName},
+    chord::{Chord, Chordable, HasChord, HasRoot, HasScale},
+    interval::Interval,
+    known_chord::HasRelativeScale,
+    named_pitch::HasNamedPitch,
+    note::Note,
+    octave::HasOctave,
+    pitch::{HasPitch, ALL_PITCHES},
+};
+
+// Enum.
+
+/// An enum representing the kind of a scale.
+#[derive(PartialEq, Eq, Copy, Clone, Hash, Debug, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[repr(u8)]
+pub enum ScaleKind {
+    /// The major (ionian) scale.
+    Major,
+    /// The natural minor (aeolian) scale.
+    NaturalMinor,
+    /// The harmonic minor scale (i.e., natural minor with a raised seventh).
+    HarmonicMinor,
+    /// The (ascending) melodic minor scale (i.e., natural minor with a raised sixth and seventh).
+    MelodicMinor,
+    /// The dorian mode.
+    Dorian,
+    /// The phrygian mode.
+    Phrygian,
+    /// The lydian mode.
+    Lydian,
+    /// The mixolydian mode.
+    Mixolydian,
+    /// The locrian mode.
+    Locrian,
+}
+
+/// An enum representing the role of a note with respect to the chord being played.
+#[derive(PartialEq, Eq, Copy, Clone, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ToneRole {
+    /// The note is a tone of the chord.
+    ChordTone,
+    /// The note is not a tone of the chord (e.g., a passing tone, or a note outside of the key).
+    PassingTone,
+}
+
+// Struct.
+
+/// A scale, defined by its root note and its kind.
+#[derive(PartialEq, Eq, Copy, Clone, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Scale {
+    /// The root note of the scale.
+    root: Note,
+    /// The kind of the scale.
+    kind: ScaleKind,
+}
+
+/// A note, annotated with its scale degree in a key, and its role with respect to a chord.
+#[derive(PartialEq, Eq, Copy, Clone, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AnnotatedNote {
+    /// The note.
+    pub note: Note,
+    /// The (1-based) scale degree of the note, or `None` if the note is not in the scale.
+    pub degree: Option<u8>,
+    /// The role of the note with respect to the chord.
+    pub role: ToneRole,
+}
+
+// Impls.
+
+impl Scale {
+    /// Returns a new scale with the given root and kind.
+    pub fn new(root: Note, kind: ScaleKind) -> Self {
+        Self { root, kind }
+    }
+
+    /// Returns the kind of the scale.
+    pub fn kind(&self) -> ScaleKind {
+        self.kind
+    }
+
+    /// Returns the notes of the scale, ascending from the root (i.e., [`HasScale::scale`]).
+    pub fn notes(&self) -> Vec<Note> {
+        self.scale()
+    }
+
+    /// Detects the scales that the given notes fit, ranked by how many of the notes they contain (so that a few notes
+    /// outside of the scale, e.g., from a noisy detection, do not rule it out).
+    ///
+    /// Ties are broken in favor of scales rooted on the lowest note, and then in the order of [`ALL_SCALE_KINDS`] (so a major
+    /// scale is preferred to its modes).  Each root is spelled with sharps or flats, whichever gives the scale fewer
+    /// accidentals.  Scales that contain none of the notes are omitted.
+    pub fn detect(notes: &[Note]) -> Vec<Scale> {
+        let Some(bass) = notes.iter().min() else {
+            return Vec::new();
+        };
+
+        let mut candidates = Vec::new();
+
+        for kind in ALL_SCALE_KINDS.iter() {
+            for pitch in ALL_PITCHES.iter() {
+                let scale = Scale::new(Note::from_pitch(*pitch, 4), *kind);
+
+                // Respell flat roots as sharps (e.g., D♭ as C♯), if that gives the scale fewer accidentals.
+                let scale = if count_accidentals(&[scale.root]) > 0 {
+                    let sharp = Scale::new(Note::new(scale.root.named_pitch() + 12, scale.root.octave()), *kind);
+
+                    if count_accidentals(&sharp.scale()) < count_accidentals(&scale.scale()) {
+                        sharp
+                    } else {
+                        scale
+                    }
+                } else {
+                    scale
+                };
+
+                let pitches = scale.scale().into_iter().map(|n| n.pitch()).collect::<Vec<_>>();
+                let count = notes.iter().filter(|n| pitches.contains(&n.pitch())).count();
+
+                if count > 0 {
+                    candidates.push((scale, count, scale.root.pitch() == bass.pitch()));
+                }
+            }
+        }
+
+        // The sort is stable, so ties keep the order of the kinds.
+        candidates.sort_by(|(_, a_count, a_is_on_bass), (_, b_count, b_is_on_bass)| (b_count, b_is_on_bass).cmp(&(a_count, a_is_on_bass)));
+
+        candidates.into_iter().map(|(scale, _, _)| scale).collect()
+    }
+
+    /// Returns the notes of the scale that are also chord tones of the given chord.
+    pub fn chord_tones(&self, chord: &Chord) -> Vec<Note> {
+        let chord_pitches = chord.chord().into_iter().map(|n| n.pitch()).collect::<Vec<_>>();
+
+        self.scale().into_iter().filter(|n| chord_pitches.contains(&n.pitch())).collect()
+    }
+
+    /// Returns the notes of the scale that are _not_ chord tones of the given chord (i.e., the passing tones).
+    pub fn non_chord_tones(&self, chord: &Chord) -> Vec<Note> {
+        let chord_pitches = chord.chord().into_iter().map(|n| n.pitch()).collect::<Vec<_>>();
+
+        self.scale().into_iter().filter(|n| !chord_pitches.contains(&n.pitch())).collect()
+    }
+
+    /// Returns the (1-based) scale degree of the given note, or `None` if the note is not in the scale.
+    pub fn degree_of(&self, note: Note) -> Option<u8> {
+        self.scale().into_iter().position(|n| n.pitch() == note.pitch()).map(|k| k as u8 + 1)
+    }
+
+    /// Returns the diatonic triad that best covers the given notes (i.e., contains the most of them).
+    ///
+    /// Ties are broken in favor of the triad whose root is the lowest note, and then in scale order.
+    pub fn best_diatonic_chord(&self, notes: &[Note]) -> Option<Chord> {
+        let bass = notes.iter().min()?;
+
+        let mut best: Option<(Chord, usize, bool)> = None;
+
+        for chord in self.diatonic_chords() {
+            let chord_pitches = chord.chord().into_iter().map(|n| n.pitch()).collect::<Vec<_>>();
+            let count = notes.iter().filter(|n| chord_pitches.contains(&n.pitch())).count();
+            let is_on_bass = chord.root().pitch() == bass.pitch();
+
+            match &best {
+                Some((_, best_count, best_is_on_bass)) if (*best_count, *best_is_on_bass) >= (count, is_on_bass) => {}
+                _ => best = Some((chord, count, is_on_bass)),
+            }
+        }
+
+        best.map(|(chord, _, _)| chord)
+    }
+
+    /// Annotates each of the given notes with its scale degree, and whether or not it is a tone of the given chord.
+    pub fn annotate(&self, notes: &[Note], chord: &Chord) -> Vec<AnnotatedNote> {
+        let chord_pitches = chord.chord().into_iter().map(|n| n.pitch()).collect::<Vec<_>>();
+
+        notes
+            .iter()
+            .map(|n| AnnotatedNote {
+                note: *n,
+                degree: self.degree_of(*n),
+                role: if chord_pitches.contains(&n.pitch()) { ToneRole::ChordTone } else { ToneRole::PassingTone },
+            })
+            .collect()
+    }
+
+    /// Returns the triads built on each degree of the scale (i.e., the chords available in the key), in order.
+    pub fn diatonic_chords(&self) -> Vec<Chord> {
+        let scale = self.scale();
+
+        (0..scale.len())
+            .map(|k| {
+                let root = scale[k];
+                let semitones_to = |n: Note| (n.pitch() as u8 + 12 - root.pitch() as u8) % 12;
+
+                let third = semitones_to(scale[(k + 2) % scale.len()]);
+                let fifth = semitones_to(scale[(k + 4) % scale.len()]);
+
+                match (third, fifth) {
+                    (3, 6) => Chord::new(root).diminished(),
+                    (3, _) => Chord::new(root).minor(),
+                    (4, 8) => Chord::new(root).augmented(),
+                    _ => Chord::new(root),
+                }
+            })
+            .collect()
+    }
+}
+
+impl HasStaticName for ScaleKind {
+    #[coverage(off)]
+    fn static_name(&self) -> &'static str {
+        match self {
+            ScaleKind::Major => "major",
+            ScaleKind::NaturalMinor => "minor",
+            ScaleKind::HarmonicMinor => "harmonic minor",
+            ScaleKind::MelodicMinor => "melodic minor",
+            ScaleKind::Dorian => "dorian",
+            ScaleKind::Phrygian => "phrygian",
+            ScaleKind::Lydian => "lydian",
+            ScaleKind::Mixolydian => "mixolydian",
+            ScaleKind::Locrian => "locrian",
+        }
+    }
+}
+
+impl HasRelativeScale for ScaleKind {
+    fn relative_scale(&self) -> Vec<Interval> {
+        match self {
+            ScaleKind::Major => vec![
+                Interval::PerfectUnison,
+                Interval::MajorSecond,
+                Interval::MajorThird,
+                Interval::PerfectFourth,
+                Interval::PerfectFifth,
+                Interval::MajorSixth,
+                Interval::MajorSeventh,
+            ],
+            ScaleKind::NaturalMinor => vec![
+                Interval::PerfectUnison,
+                Interval::MajorSecond,
+                Interval::MinorThird,
+                Interval::PerfectFourth,
+                Interval::PerfectFifth,
+                Interval::MinorSixth,
+                Interval::MinorSeventh,
+            ],
+            ScaleKind::HarmonicMinor => vec![
+                Interval::PerfectUnison,
+                Interval::MajorSecond,
+                Interval::MinorThird,
+                Interval::PerfectFourth,
+                Interval::PerfectFifth,
+                Interval::MinorSixth,
+                Interval::MajorSeventh,
+            ],
+            ScaleKind::MelodicMinor => vec![
+                Interval::PerfectUnison,
+                Interval::MajorSecond,
+                Interval::MinorThird,
+                Interval::PerfectFourth,
+                Interval::PerfectFifth,
+                Interval::MajorSixth,
+                Interval::MajorSeventh,
+            ],
+            ScaleKind::Dorian => vec![
+                Interval::PerfectUnison,
+                Interval::MajorSecond,
+                Interval::MinorThird,
+                Interval::PerfectFourth,
+                Interval::PerfectFifth,
+                Interval::MajorSixth,
+                Interval::MinorSeventh,
+            ],
+            ScaleKind::Phrygian => vec![
+                Interval::PerfectUnison,
+                Interval::MinorSecond,
+                Interval::MinorThird,
+                Interval::PerfectFourth,
+                Interval::PerfectFifth,
+                Interval::MinorSixth,
+                Interval::MinorSeventh,
+            ],
+            ScaleKind::Lydian => vec![
+                Interval::PerfectUnison,
+                Interval::MajorSecond,
+                Interval::MajorThird,
+                Interval::AugmentedFourth,
+                Interval::PerfectFifth,
+                Interval::MajorSixth,
+                Interval::MajorSeventh,
+            ],
+            ScaleKind::Mixolydian => vec![
+                Interval::PerfectUnison,
+                Interval::MajorSecond,
+                Interval::MajorThird,
+                Interval::PerfectFourth,
+                Interval::PerfectFifth,
+                Interval::MajorSixth,
+                Interval::MinorSeventh,
+            ],
+            ScaleKind::Locrian => vec![
+                Interval::PerfectUnison,
+                Interval::MinorSecond,
+                Interval::MinorThird,
+                Interval::PerfectFourth,
+                Interval::DiminishedFifth,
+                Interval::MinorSixth,
+                Interval::MinorSeventh,
+            ],
+        }
+    }
+}
+
+impl HasRelativeScale for Scale {
+    fn relative_scale(&self) -> Vec<Interval> {
+        self.kind.relative_scale()
+    }
+}
+
+impl HasRoot for Scale {
+    fn root(&self) -> Note {
+        self.root
+    }
+}
+
+impl HasScale for Scale {
+    fn scale(&self) -> Vec<Note> {
+        self.relative_scale().into_iter().map(|i| self.root + i).collect()
+    }
+}
+
+impl HasName for Scale {
+    fn name(&self) -> String {
+        format!("{} {}", self.root.static_name(), self.kind.static_name())
+    }
+}
+
+// Statics.
+
+/// All of the scale kinds, in order of preference (see [`Scale::detect`]).
+pub static ALL_SCALE_KINDS: [ScaleKind; 9] = [
+    ScaleKind::Major,
+    ScaleKind::NaturalMinor,
+    ScaleKind::HarmonicMinor,
+    ScaleKind::MelodicMinor,
+    ScaleKind::Dorian,
+    ScaleKind::Phrygian,
+    ScaleKind::Lydian,
+    ScaleKind::Mixolydian,
+    ScaleKind::Locrian,
+];
+
+// Helpers.
+
+/// Counts the accidentals in the given notes (e.g., a double sharp counts twice).
+fn count_accidentals(notes: &[Note]) -> usize {
+    // The named pitches are ordered by fifths, so each group of seven shares an accidental (and the naturals are the fourth).
+    notes.iter().map(|n| (n.named_pitch() as i8 / 7 - 3).unsigned_abs() as usize).sum()
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::note::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_scale() {
+        assert_eq!(Scale::new(C, ScaleKind::Major).scale(), vec![C, D, E, F, G, A, B]);
+        assert_eq!(Scale::new(A, ScaleKind::NaturalMinor).scale(), vec![A, B, CFive, DFive, EFive, FFive, GFive]);
+        assert_eq!(Scale::new(EFlat, ScaleKind::Major).name(), "E♭ major");
+    }
+
+    #[test]
+    fn test_kinds() {
+        assert_eq!(Scale::new(A, ScaleKind::HarmonicMinor).notes(), vec![A, B, CFive, DFive, EFive, FFive, GSharpFive]);
+        assert_eq!(Scale::new(A, ScaleKind::MelodicMinor).notes(), vec![A, B, CFive, DFive, EFive, FSharpFive, GSharpFive]);
+        assert_eq!(Scale::new(D, ScaleKind::Dorian).notes(), vec![D, E, F, G, A, B, CFive]);
+        assert_eq!(Scale::new(E, ScaleKind::Phrygian).notes(), vec![E, F, G, A, B, CFive, DFive]);
+        assert_eq!(Scale::new(F, ScaleKind::Lydian).notes(), vec![F, G, A, B, CFive, DFive, EFive]);
+        assert_eq!(Scale::new(G, ScaleKind::Mixolydian).notes(), vec![G, A, B, CFive, DFive, EFive, FFive]);
+        assert_eq!(Scale::new(B, ScaleKind::Locrian).notes(), vec![B, CFive, DFive, EFive, FFive, GFive, AFive]);
+        assert_eq!(Scale::new(D, ScaleKind::HarmonicMinor).name(), "D harmonic minor");
+
+        let names = |scale: Scale| scale.diatonic_chords().into_iter().map(|c| c.name()).collect::<Vec<_>>();
+        assert_eq!(names(Scale::new(A, ScaleKind::HarmonicMinor)), vec!["Am", "Bdim", "C+", "Dm", "E", "F", "G♯dim"]);
+    }
+
+    #[test]
+    fn test_detect() {
+        // A full major scale.
+        let detected = Scale::detect(&[C, D, E, F, G, A, B]);
+        assert_eq!(detected[0], Scale::new(C, ScaleKind::Major));
+
+        // The same notes from the sixth are the relative minor.
+        assert_eq!(Scale::detect(&[AThree, B, C, D, E, F, G])[0], Scale::new(A, ScaleKind::NaturalMinor));
+
+        // A raised seventh makes it harmonic minor.
+        assert_eq!(Scale::detect(&[A, B, CFive, DFive, EFive, FFive, GSharpFive])[0], Scale::new(A, ScaleKind::HarmonicMinor));
+
+        // A few wrong notes still rank the right scale first.
+        let detected = Scale::detect(&[G, A, B, CFive, DFive, EFive, FSharpFive, GFive, BFive, DFlatFive]);
+        assert_eq!(detected[0], Scale::new(G, ScaleKind::Major));
+
+        // Roots are spelled with whichever of sharps or flats gives fewer accidentals.
+        assert_eq!(Scale::detect(&[CSharp, DSharp, ESharp, FSharp, GSharp, ASharp, BSharp])[0].name(), "D♭ major");
+        assert_eq!(Scale::detect(&[FSharp, GSharp, A, B, CSharpFive, DFive, EFive])[0].name(), "F♯ minor");
+        assert_eq!(Scale::detect(&[EFlat, F, G, AFlat, BFlat, CFive, DFive])[0].name(), "E♭ major");
+
+        assert_eq!(Scale::detect(&[]), vec![]);
+    }
+
+    #[test]
+    fn test_chord_tones() {
+        let scale = Scale::new(C, ScaleKind::Major);
+        let chord = Chord::new(C);
+
+        assert_eq!(scale.chord_tones(&chord), vec![C, E, G]);
+        assert_eq!(scale.non_chord_tones(&chord), vec![D, F, A, B]);
+    }
+
+    #[test]
+    fn test_diatonic_chords() {
+        let names = |scale: Scale| scale.diatonic_chords().into_iter().map(|c| c.name()).collect::<Vec<_>>();
+
+        assert_eq!(names(Scale::new(C, ScaleKind::Major)), vec!["C", "Dm", "Em", "F", "G", "Am", "Bdim"]);
+        assert_eq!(names(Scale::new(A, ScaleKind::NaturalMinor)), vec!["Am", "Bdim", "C", "Dm", "Em", "F", "G"]);
+    }
+
+    #[test]
+    fn test_annotate() {
+        let scale = Scale::new(C, ScaleKind::Major);
+        let notes = [C, E, G, B, FSharp];
+
+        assert_eq!(scale.degree_of(BFive), Some(7));
+        assert_eq!(scale.degree_of(FSharp), None);
+
+        let chord = scale.best_diatonic_chord(&notes).unwrap();
+        assert_eq!(chord, Chord::new(C));
+
+        let annotated = scale.annotate(&notes, &chord);
+        assert_eq!(annotated.iter().map(|a| a.degree).collect::<Vec<_>>(), vec![Some(1), Some(3), Some(5), Some(7), None]);
+        assert_eq!(
+            annotated.iter().map(|a| a.role).collect::<Vec<_>>(),
+            vec![ToneRole::ChordTone, ToneRole::ChordTone, ToneRole::ChordTone, ToneRole::PassingTone, ToneRole::PassingTone]
+        );
+
+        assert_eq!(scale.best_diatonic_chord(&[]), None);
+    }
+}
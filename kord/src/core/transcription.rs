@@ -0,0 +1,143 @@
+//! A module for working with (structured) transcriptions of music.
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::core::{
+    chord::{Chord, HasRoot},
+    midi::NoteEvent,
+    named_pitch::HasNamedPitch,
+    note::Note,
+    pitch::HasPitch,
+    scale::Scale,
+};
+
+// Struct.
+
+/// A transcription of a piece of music: its notes, chords, key, and beats.
+#[derive(PartialEq, Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Transcription {
+    /// The notes, with their timing.
+    pub notes: Vec<NoteEvent>,
+    /// The chords, as (start time in seconds, chord) pairs.
+    pub chords: Vec<(f32, Chord)>,
+    /// The key, if it is known.
+    pub key: Option<Scale>,
+    /// The beats (in seconds).
+    pub beats: Vec<f32>,
+}
+
+// Impls.
+
+impl Transcription {
+    /// Creates a new transcription.
+    pub fn new(notes: Vec<NoteEvent>, chords: Vec<(f32, Chord)>, key: Option<Scale>, beats: Vec<f32>) -> Self {
+        Self { notes, chords, key, beats }
+    }
+
+    /// Returns the transcription, transposed by the given number of semitones (negative values transpose down).
+    ///
+    /// Every note, chord, and the key are shifted, while the timing (and the beats) are preserved.  Each chord keeps its
+    /// quality, extensions, and inversion (see [`Chord::transpose`]).  If the key is known, the notes (and the roots, and
+    /// slashes, of the chords) that are in the transposed key are spelled as they are in it (e.g., B up a tritone is E♯ in
+    /// F♯ major), and the others are spelled relative to it (see [`Note::transpose`]).
+    pub fn transpose(&self, semitones: i32) -> Transcription {
+        let steps = semitone_steps(semitones);
+
+        let key = self.key.map(|k| Scale::new(steps.iter().fold(k.root(), |n, &s| n.transpose(s)), k.kind()));
+        let key_notes = key.map(|k| k.notes()).unwrap_or_default();
+
+        let spell = |note: Note| match key_notes.iter().find(|n| n.pitch() == note.pitch()) {
+            Some(n) => note.respell_as(n.named_pitch()),
+            None => note,
+        };
+
+        let notes = self
+            .notes
+            .iter()
+            .map(|e| NoteEvent::new(spell(steps.iter().fold(e.note, |n, &s| n.transpose(s))), e.start, e.duration))
+            .collect();
+
+        let chords = self
+            .chords
+            .iter()
+            .map(|(t, c)| (*t, steps.iter().fold(c.clone(), |c, &s| c.transpose(s)).respell_with(spell)))
+            .collect();
+
+        Transcription::new(notes, chords, key, self.beats.clone())
+    }
+}
+
+// Helpers.
+
+/// Splits the semitones into steps that each fit in an `i8` (which notes, and chords, are transposed by).
+fn semitone_steps(semitones: i32) -> Vec<i8> {
+    let mut remaining = semitones;
+    let mut steps = Vec::new();
+
+    while remaining != 0 {
+        let step = remaining.clamp(i8::MIN as i32, i8::MAX as i32);
+
+        steps.push(step as i8);
+        remaining -= step;
+    }
+
+    steps
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{
+        base::{HasName, Parsable},
+        note::*,
+        octave::Octave,
+        scale::ScaleKind,
+    };
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_transpose() {
+        let chords = ["C", "Am7", "F/A", "G7"]
+            .iter()
+            .enumerate()
+            .map(|(k, c)| (k as f32 * 2.0, Chord::parse(c).unwrap()))
+            .collect::<Vec<_>>();
+        let notes = vec![NoteEvent::new(C, 0.0, 1.0), NoteEvent::new(E, 0.5, 1.5), NoteEvent::new(B, 6.0, 2.0)];
+        let beats = vec![0.0, 0.5, 1.0, 1.5];
+
+        let transcription = Transcription::new(notes, chords, Some(Scale::new(C, ScaleKind::Major)), beats.clone());
+
+        // Up a fifth.
+        let transposed = transcription.transpose(7);
+
+        assert_eq!(transposed.chords.iter().map(|(_, c)| c.name()).collect::<Vec<_>>(), vec!["G", "Em7", "C/E", "D7"]);
+        assert_eq!(transposed.chords.iter().map(|(t, _)| *t).collect::<Vec<_>>(), vec![0.0, 2.0, 4.0, 6.0]);
+        assert_eq!(transposed.notes, vec![NoteEvent::new(G, 0.0, 1.0), NoteEvent::new(B, 0.5, 1.5), NoteEvent::new(FSharpFive, 6.0, 2.0)]);
+        assert_eq!(transposed.key, Some(Scale::new(G, ScaleKind::Major)));
+        assert_eq!(transposed.beats, beats);
+
+        // And back down.
+        assert_eq!(transposed.transpose(-7), transcription);
+
+        // Up a tritone, the notes, and chords, are spelled in F♯ major.
+        let transposed = transcription.transpose(6);
+
+        assert_eq!(transposed.chords.iter().map(|(_, c)| c.name()).collect::<Vec<_>>(), vec!["F♯", "D♯m7", "B/D♯", "C♯7"]);
+        assert_eq!(transposed.notes.iter().map(|e| e.note).collect::<Vec<_>>(), vec![FSharp, ASharp, ESharpFive]);
+        assert_eq!(transposed.key, Some(Scale::new(FSharp, ScaleKind::Major)));
+
+        // Intervals wider than an `i8` are transposed, too.
+        let low = Transcription::new(vec![NoteEvent::new(C.with_octave(Octave::One), 0.0, 1.0)], vec![], Some(Scale::new(C, ScaleKind::Major)), vec![]);
+        let high = low.transpose(132);
+
+        assert_eq!(high.notes, vec![NoteEvent::new(C.with_octave(Octave::Twelve), 0.0, 1.0)]);
+        assert_eq!(high.transpose(-132), low);
+        assert_eq!(semitone_steps(300), vec![127, 127, 46]);
+        assert_eq!(semitone_steps(-130), vec![-128, -2]);
+        assert_eq!(semitone_steps(0), Vec::<i8>::new());
+    }
+}
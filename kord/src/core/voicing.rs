@@ -0,0 +1,161 @@
+//! A module for working with voicings (i.e., the notes of a chord, as assigned to voices).
+
+use crate::core::{interval::SpelledInterval, note::Note};
+
+// Enum.
+
+/// The kind of forbidden parallel motion between two voices.
+#[derive(PartialEq, Eq, Copy, Clone, Hash, Debug)]
+pub enum ParallelKind {
+    /// Parallel (perfect) fifths, including compound fifths.
+    Fifths,
+    /// Parallel octaves, including unisons and compound octaves.
+    Octaves,
+}
+
+// Struct.
+
+/// A parallel fifth or octave between two voices of consecutive voicings.
+#[derive(PartialEq, Eq, Copy, Clone, Hash, Debug)]
+pub struct ParallelMotion {
+    /// The indexes of the two voices (lower index first).
+    pub voices: (usize, usize),
+    /// The kind of parallel motion.
+    pub kind: ParallelKind,
+}
+
+// Functions.
+
+/// Returns the motion of each voice between two voicings (i.e., the interval from `from[k]` to `to[k]`, which is descending
+/// when the voice moves down).
+///
+/// The motions are [`SpelledInterval`]s, rather than [`Interval`](crate::core::interval::Interval)s, since a voice can move in
+/// either direction, and by any interval (see [`SpelledInterval::semitones`] for the signed size of each motion).
+///
+/// Voices are matched by index, so both voicings should list their voices in the same order (e.g., bass to soprano).  If the
+/// voicings have a different number of voices, the extra voices are ignored.
+pub fn voice_leading(from: &[Note], to: &[Note]) -> Vec<SpelledInterval> {
+    from.iter().zip(to).map(|(f, t)| f.interval_to(t)).collect()
+}
+
+/// Returns the parallel fifths and octaves between two voicings.
+///
+/// A pair of voices moves in parallel when both voices move in the same direction, and they form a perfect fifth (or a
+/// perfect octave / unison), up to compound intervals, in both voicings.
+pub fn parallel_motion(from: &[Note], to: &[Note]) -> Vec<ParallelMotion> {
+    let num_voices = from.len().min(to.len());
+    let mut result = Vec::new();
+
+    for lower in 0..num_voices {
+        for upper in (lower + 1)..num_voices {
+            let lower_motion = direction(from[lower], to[lower]);
+            let upper_motion = direction(from[upper], to[upper]);
+
+            if lower_motion == 0 || lower_motion != upper_motion {
+                continue;
+            }
+
+            let before = semitones_between(from[lower], from[upper]).rem_euclid(12);
+            let after = semitones_between(to[lower], to[upper]).rem_euclid(12);
+
+            let kind = match (before, after) {
+                (7, 7) => ParallelKind::Fifths,
+                (0, 0) => ParallelKind::Octaves,
+                _ => continue,
+            };
+
+            result.push(ParallelMotion { voices: (lower, upper), kind });
+        }
+    }
+
+    result
+}
+
+// Helpers.
+
+/// Returns the (signed) number of semitones from `a` to `b`, counting enharmonic notes as unisons (e.g., B♯3 to C4 is `0`).
+fn semitones_between(a: Note, b: Note) -> i16 {
+    a.interval_to(&b).semitones()
+}
+
+/// Returns the direction of the motion from `a` to `b` (i.e., `-1`, `0`, or `1`).
+fn direction(a: Note, b: Note) -> i16 {
+    semitones_between(a, b).signum()
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{base::HasName, note::*};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_voice_leading() {
+        // C major to G major (bass, tenor, alto, soprano), with smooth inner voices.
+        let from = [CThree, GThree, C, E];
+        let to = [GTwo, GThree, BThree, D];
+
+        // The bass leaps down a fourth, and the alto, and soprano, step down.
+        let motion = voice_leading(&from, &to);
+
+        assert_eq!(
+            motion.iter().map(|m| m.name()).collect::<Vec<_>>(),
+            vec!["perfect fourth", "perfect unison", "minor second", "major second"]
+        );
+        assert_eq!(motion.iter().map(|m| m.is_descending()).collect::<Vec<_>>(), vec![true, false, true, true]);
+        assert_eq!(motion.iter().map(|m| m.semitones()).collect::<Vec<_>>(), vec![-5, 0, -1, -2]);
+        assert_eq!(voice_leading(&to, &from).iter().map(|m| m.semitones()).collect::<Vec<_>>(), vec![5, 0, 1, 2]);
+        assert_eq!(parallel_motion(&from, &to), vec![]);
+    }
+
+    #[test]
+    fn test_voice_leading_enharmonic() {
+        // B♯3 and C4 are in unison, so a voice that is respelled does not move (and the octave into a unison is oblique).
+        assert_eq!(voice_leading(&[BSharpThree], &[C])[0].semitones(), 0);
+        assert_eq!(voice_leading(&[C], &[BSharpThree])[0].semitones(), 0);
+        assert_eq!(parallel_motion(&[CThree, BSharpThree], &[C, C]), vec![]);
+        assert_eq!(
+            parallel_motion(&[CThree, BSharpThree], &[DThree, DFive]),
+            vec![ParallelMotion {
+                voices: (0, 1),
+                kind: ParallelKind::Octaves
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parallel_fifths() {
+        // C major to D minor, with every voice moving up a step.
+        let from = [CThree, GThree, C, E];
+        let to = [DThree, AThree, D, F];
+
+        assert_eq!(
+            parallel_motion(&from, &to),
+            vec![
+                ParallelMotion {
+                    voices: (0, 1),
+                    kind: ParallelKind::Fifths
+                },
+                ParallelMotion {
+                    voices: (0, 2),
+                    kind: ParallelKind::Octaves
+                },
+            ]
+        );
+
+        // Oblique and contrary motion into a fifth are fine.
+        assert_eq!(parallel_motion(&[CThree, GThree], &[CThree, G]), vec![]);
+        assert_eq!(parallel_motion(&[CThree, GThree], &[ATwo, E]), vec![]);
+
+        // Parallel compound fifths are still parallel fifths.
+        assert_eq!(
+            parallel_motion(&[CThree, G], &[DThree, A]),
+            vec![ParallelMotion {
+                voices: (0, 1),
+                kind: ParallelKind::Fifths
+            }]
+        );
+    }
+}
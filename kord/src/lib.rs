@@ -58,8 +58,11 @@ pub mod analyze;
 #[cfg(feature = "ml_base")]
 pub mod ml;
 
+#[cfg(feature = "osc")]
+pub mod osc;
+
 #[cfg(feature = "wasm")]
 pub mod wasm;
 
-#[cfg(feature = "analyze_base")]
+#[cfg(any(feature = "audio", feature = "analyze_mic", feature = "analyze_file"))]
 pub use rodio;
@@ -1,61 +1,214 @@
 //! Module for executing inference.
 
+use std::{
+    collections::HashMap,
+    sync::{Arc, LazyLock, Mutex},
+};
+
 use burn::{
     config::Config,
     module::Module,
     record::{BinBytesRecorder, FullPrecisionSettings, Recorder},
-    tensor::backend::Backend,
+    tensor::{backend::Backend, Tensor},
 };
 use burn_ndarray::{NdArray, NdArrayDevice};
+use rayon::{ThreadPool, ThreadPoolBuilder};
 use serde::{de::DeserializeOwned, Serialize};
 
 use crate::{
-    analyze::base::{get_frequency_space, get_smoothed_frequency_space},
+    analyze::base::{get_frequency_space, get_smoothed_frequency_space, is_silent, sanitize_audio_data},
     core::{
-        base::Res,
+        base::{KordError, Res},
         note::{HasNoteId, Note},
     },
-    ml::base::{data::kord_item_to_sample_tensor, helpers::binary_to_u128, model::KordModel, KordItem, TrainConfig, FREQUENCY_SPACE_SIZE},
+    ml::base::{data::kord_item_to_sample_tensor, helpers::binary_to_u128, model::KordModel, KordItem, TrainConfig, FREQUENCY_SPACE_SIZE, NUM_CLASSES},
 };
 
-/// Run the inference on a sample to produce a [`Vec`] of [`Note`]s.
-pub fn run_inference<B: Backend>(device: &B::Device, kord_item: &KordItem) -> Res<Vec<Note>>
+// Enum.
+
+/// The execution provider (i.e., the backend, and device) that inference runs on.
+#[derive(PartialEq, Eq, Copy, Clone, Hash, Debug, Default)]
+pub enum ExecutionProvider {
+    /// The CPU (via `burn-ndarray`).
+    #[default]
+    Cpu,
+    /// The GPU, via LibTorch (CUDA, or MPS on macOS), which requires the `ml_gpu` feature.
+    Gpu,
+    /// The GPU, via WebGPU, which requires the `ml_gpu` feature.
+    Wgpu,
+}
+
+// Struct.
+
+/// Options for running inference (see [`run_inference`]).
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct InferenceOptions {
+    /// The preferred execution provider, which falls back to [`ExecutionProvider::Cpu`] if it is not compiled in, or fails
+    /// to initialize.
+    pub provider: ExecutionProvider,
+    /// The number of threads that inference on the CPU may use (or, if `None`, the default of one per core).
+    pub num_threads: Option<usize>,
+}
+
+impl InferenceOptions {
+    /// Sets the preferred execution provider, and returns new options.
+    pub fn with_provider(self, provider: ExecutionProvider) -> Self {
+        Self { provider, ..self }
+    }
+
+    /// Sets the number of threads that inference on the CPU may use, and returns new options.
+    pub fn with_num_threads(self, num_threads: usize) -> Self {
+        Self { num_threads: Some(num_threads), ..self }
+    }
+}
+
+// Functions.
+
+/// Run the inference on a sample to produce a [`Vec`] of [`Note`]s, using the given options (or the defaults, if `None`).
+///
+/// Returns the notes, along with the execution provider that was actually used: if the preferred provider is not compiled
+/// in, or fails to initialize, inference falls back to the CPU.
+pub fn run_inference(kord_item: &KordItem, options: Option<&InferenceOptions>) -> Res<(Vec<Note>, ExecutionProvider)> {
+    let (mut notes, provider) = run_inference_batch_with_options(std::slice::from_ref(kord_item), options)?;
+
+    Ok((notes.remove(0), provider))
+}
+
+/// Run the inference on a sample, on the given backend and device, to produce a [`Vec`] of [`Note`]s.
+pub fn run_inference_with_backend<B: Backend>(device: &B::Device, kord_item: &KordItem) -> Res<Vec<Note>>
 where
     B::FloatElem: Serialize + DeserializeOwned,
 {
+    Ok(run_inference_batch_with_backend::<B>(device, std::slice::from_ref(kord_item))?.remove(0))
+}
+
+/// Run the inference on a batch of samples, on the given backend and device, in a single forward pass, to produce one
+/// [`Vec`] of [`Note`]s per sample (in order).
+pub fn run_inference_batch_with_backend<B: Backend>(device: &B::Device, kord_items: &[KordItem]) -> Res<Vec<Vec<Note>>>
+where
+    B::FloatElem: Serialize + DeserializeOwned,
+{
+    if kord_items.is_empty() {
+        return Ok(Vec::new());
+    }
+
     // Load the config and state.
 
     let config = match TrainConfig::load_binary(CONFIG) {
         Ok(config) => config,
         Err(e) => {
-            return Err(anyhow::Error::msg(format!("Could not load the config from within the binary: {e}.")));
+            return Err(KordError::InferenceError(format!("Could not load the config from within the binary: {e}.")).into());
         }
     };
 
     let recorder = match BinBytesRecorder::<FullPrecisionSettings>::new().load(Vec::from_iter(STATE_BINCODE.iter().cloned()), device) {
         Ok(recorder) => recorder,
         Err(_) => {
-            return Err(anyhow::Error::msg("Could not load the state from within the binary."));
+            return Err(KordError::InferenceError("Could not load the state from within the binary.".to_string()).into());
         }
     };
 
     // Define the model.
     let model = KordModel::<B>::new(device, config.mha_heads, config.mha_dropout, config.sigmoid_strength).load_record(recorder);
 
-    // Prepare the sample.
-    let sample = kord_item_to_sample_tensor(device, kord_item).detach();
+    // Prepare the samples (stacked into one tensor, with one row per sample).
+    let samples = Tensor::cat(kord_items.iter().map(|item| kord_item_to_sample_tensor(device, item)).collect(), 0).detach();
 
     // Run the inference.
-    let inferred = model.forward(sample).to_data().iter().map(f32::round).collect::<Vec<_>>();
-    let inferred_array: [_; 128] = inferred.try_into().unwrap();
-    let mut inferred_notes = Note::from_id_mask(binary_to_u128(&inferred_array)).unwrap();
-    inferred_notes.sort();
+    let inferred = model.forward(samples).to_data().iter().map(f32::round).collect::<Vec<_>>();
+
+    let inferred_notes = inferred
+        .chunks_exact(NUM_CLASSES)
+        .map(|row| {
+            let mut notes = Note::from_id_mask(binary_to_u128(row)).unwrap();
+            notes.sort();
+
+            notes
+        })
+        .collect();
 
     Ok(inferred_notes)
 }
 
+/// Run the inference on a batch of audio windows (e.g., the overlapping windows of a long recording), in a single forward
+/// pass, using the given options (or the defaults, if `None`), to produce one [`Vec`] of [`Note`]s per window (in order).
+///
+/// All of the windows must be the same length (which is `length_in_seconds` long); silent windows have no notes, and an
+/// empty batch has no results.
+///
+/// Returns the notes, along with the execution provider that was actually used (see [`run_inference`]), which is `None` if
+/// every window is silent (and no inference was needed).
+pub fn run_inference_batch(windows: &[Vec<f32>], length_in_seconds: f32, options: Option<&InferenceOptions>) -> Res<(Vec<Vec<Note>>, Option<ExecutionProvider>)> {
+    let Some(first) = windows.first() else {
+        return Ok((Vec::new(), None));
+    };
+
+    if let Some(k) = windows.iter().position(|w| w.len() != first.len()) {
+        return Err(KordError::InvalidArgument(format!(
+            "All of the windows in a batch must be the same length (window {k} has {} samples, but window 0 has {}).",
+            windows[k].len(),
+            first.len()
+        ))
+        .into());
+    }
+
+    // Only the windows that are not silent need to run through the model.
+
+    let mut is_sounding = Vec::with_capacity(windows.len());
+    let mut kord_items = Vec::with_capacity(windows.len());
+
+    for window in windows {
+        let audio_data = sanitize_audio_data(window);
+
+        let sounding = !is_silent(&audio_data);
+
+        if sounding {
+            kord_items.push(audio_data_to_kord_item(&audio_data, length_in_seconds));
+        }
+
+        is_sounding.push(sounding);
+    }
+
+    if kord_items.is_empty() {
+        return Ok((vec![Vec::new(); windows.len()], None));
+    }
+
+    let (inferred, provider) = run_inference_batch_with_options(&kord_items, options)?;
+    let mut inferred = inferred.into_iter();
+
+    Ok((is_sounding.into_iter().map(|s| if s { inferred.next().unwrap() } else { Vec::new() }).collect(), Some(provider)))
+}
+
 /// Infer notes from the audio data.
 pub fn infer(audio_data: &[f32], length_in_seconds: f32) -> Res<Vec<Note>> {
+    Ok(infer_with_options(audio_data, length_in_seconds, None)?.0)
+}
+
+/// Infer notes from the audio data, using the given options (or the defaults, if `None`).
+///
+/// Returns the notes, along with the execution provider that was actually used (see [`run_inference`]), which is `None` if
+/// the audio is silent (and no inference was needed).
+pub fn infer_with_options(audio_data: &[f32], length_in_seconds: f32, options: Option<&InferenceOptions>) -> Res<(Vec<Note>, Option<ExecutionProvider>)> {
+    let audio_data = sanitize_audio_data(audio_data);
+
+    // Empty and silent buffers (e.g., silent frames of a stream) have no notes.
+
+    if is_silent(&audio_data) {
+        return Ok((Vec::new(), None));
+    }
+
+    let kord_item = audio_data_to_kord_item(&audio_data, length_in_seconds);
+
+    // Run the inference.
+    let (notes, provider) = run_inference(&kord_item, options)?;
+
+    Ok((notes, Some(provider)))
+}
+
+// Helpers.
+
+/// Converts (sanitized) audio data into the item that the model runs on.
+fn audio_data_to_kord_item(audio_data: &[f32], length_in_seconds: f32) -> KordItem {
     let frequency_space = get_frequency_space(audio_data, length_in_seconds);
     let smoothed_frequency_space: [_; FREQUENCY_SPACE_SIZE] = get_smoothed_frequency_space(&frequency_space, length_in_seconds)
         .into_iter()
@@ -65,20 +218,85 @@ pub fn infer(audio_data: &[f32], length_in_seconds: f32) -> Res<Vec<Note>> {
         .try_into()
         .unwrap();
 
-    let kord_item = KordItem {
+    KordItem {
         frequency_space: smoothed_frequency_space,
         ..Default::default()
+    }
+}
+
+/// Runs the inference on a batch of samples on the preferred provider of the options (or the defaults, if `None`), falling
+/// back to the CPU (optionally, on a bounded pool of threads), and returns the notes along with the provider that was used.
+fn run_inference_batch_with_options(kord_items: &[KordItem], options: Option<&InferenceOptions>) -> Res<(Vec<Vec<Note>>, ExecutionProvider)> {
+    let options = options.cloned().unwrap_or_default();
+
+    match options.provider {
+        ExecutionProvider::Cpu => {}
+        #[cfg(feature = "ml_gpu")]
+        ExecutionProvider::Gpu => {
+            use burn_tch::{LibTorch, LibTorchDevice};
+
+            #[cfg(not(target_os = "macos"))]
+            let device = LibTorchDevice::Cuda(0);
+            #[cfg(target_os = "macos")]
+            let device = LibTorchDevice::Mps;
+
+            if let Some(notes) = try_provider(|| run_inference_batch_with_backend::<LibTorch<f32>>(&device, kord_items)) {
+                return Ok((notes, ExecutionProvider::Gpu));
+            }
+        }
+        #[cfg(feature = "ml_gpu")]
+        ExecutionProvider::Wgpu => {
+            use burn_wgpu::{Wgpu, WgpuDevice};
+
+            let device = WgpuDevice::default();
+
+            if let Some(notes) = try_provider(|| run_inference_batch_with_backend::<Wgpu>(&device, kord_items)) {
+                return Ok((notes, ExecutionProvider::Wgpu));
+            }
+        }
+        #[cfg(not(feature = "ml_gpu"))]
+        ExecutionProvider::Gpu | ExecutionProvider::Wgpu => {}
+    }
+
+    // Run on the CPU (optionally, on a bounded pool of threads).
+
+    let run = || run_inference_batch_with_backend::<NdArray<f32>>(&NdArrayDevice::Cpu, kord_items);
+
+    let notes = match options.num_threads {
+        Some(num_threads) => thread_pool(num_threads)?.install(run)?,
+        None => run()?,
     };
 
-    let device = NdArrayDevice::Cpu;
+    Ok((notes, ExecutionProvider::Cpu))
+}
 
-    // Run the inference.
-    let notes = run_inference::<NdArray<f32>>(&device, &kord_item)?;
+/// Returns the thread pool with the given number of threads, which is built on first use, and shared by every inference
+/// after that.
+fn thread_pool(num_threads: usize) -> Res<Arc<ThreadPool>> {
+    let mut pools = THREAD_POOLS.lock().unwrap();
 
-    Ok(notes)
+    if let Some(pool) = pools.get(&num_threads) {
+        return Ok(pool.clone());
+    }
+
+    let pool = Arc::new(ThreadPoolBuilder::new().num_threads(num_threads).build()?);
+    pools.insert(num_threads, pool.clone());
+
+    Ok(pool)
+}
+
+/// Runs inference on a provider that may not be available, treating both errors and panics (which is how some backends
+/// report that their device could not be initialized) as unavailability.
+#[cfg(feature = "ml_gpu")]
+fn try_provider<T>(run: impl FnOnce() -> Res<T>) -> Option<T> {
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(run)).ok().and_then(Result::ok)
 }
 
 // Statics.
+
+/// The thread pools that CPU inference runs on, by number of threads (see [`InferenceOptions::num_threads`]).
+static THREAD_POOLS: LazyLock<Mutex<HashMap<usize, Arc<ThreadPool>>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
 #[cfg(host_family_unix)]
 static CONFIG: &[u8] = include_bytes!("../../../model/model_config.json");
 #[cfg(host_family_unix)]
@@ -101,8 +319,7 @@ mod tests {
     use super::*;
     use crate::core::{base::Parsable, chord::Chord};
 
-    #[test]
-    fn test_inference() {
+    fn load_test_data() -> Vec<f32> {
         let mut file = File::open("tests/vec.bin").unwrap();
         let file_size = file.metadata().unwrap().len() as usize;
         let float_size = std::mem::size_of::<f32>();
@@ -113,7 +330,12 @@ mod tests {
         file.read_exact(&mut buffer).unwrap();
 
         // Convert the buffer to a vector of f32
-        let audio_data: Vec<f32> = unsafe { std::slice::from_raw_parts(buffer.as_ptr() as *const f32, element_count).to_vec() };
+        unsafe { std::slice::from_raw_parts(buffer.as_ptr() as *const f32, element_count).to_vec() }
+    }
+
+    #[test]
+    fn test_inference() {
+        let audio_data = load_test_data();
 
         let notes = infer(&audio_data, 5.0).unwrap();
 
@@ -121,4 +343,61 @@ mod tests {
 
         assert_eq!(chord[0], Chord::parse("C7b9").unwrap());
     }
+
+    #[test]
+    fn test_inference_options() {
+        let audio_data = load_test_data();
+        let expected = infer(&audio_data, 5.0).unwrap();
+
+        let options = InferenceOptions::default().with_num_threads(2);
+        assert_eq!(infer_with_options(&audio_data, 5.0, Some(&options)).unwrap(), (expected.clone(), Some(ExecutionProvider::Cpu)));
+
+        // Without the GPU backends compiled in, inference falls back to the CPU (and says so).
+        #[cfg(not(feature = "ml_gpu"))]
+        for provider in [ExecutionProvider::Gpu, ExecutionProvider::Wgpu] {
+            let options = InferenceOptions::default().with_provider(provider);
+
+            assert_eq!(infer_with_options(&audio_data, 5.0, Some(&options)).unwrap(), (expected.clone(), Some(ExecutionProvider::Cpu)));
+        }
+
+        assert_eq!(infer_with_options(&[], 5.0, Some(&options)).unwrap(), (vec![], None));
+
+        // The thread pool is built once, and reused.
+        assert!(Arc::ptr_eq(&thread_pool(2).unwrap(), &thread_pool(2).unwrap()));
+        assert_eq!(thread_pool(3).unwrap().current_num_threads(), 3);
+    }
+
+    #[test]
+    fn test_inference_batch() {
+        let audio_data = load_test_data();
+        let expected = infer(&audio_data, 5.0).unwrap();
+
+        let windows = vec![audio_data.clone(), vec![0.0; audio_data.len()], audio_data.clone()];
+        assert_eq!(
+            run_inference_batch(&windows, 5.0, None).unwrap(),
+            (vec![expected.clone(), vec![], expected.clone()], Some(ExecutionProvider::Cpu))
+        );
+
+        // Without the GPU backends compiled in, the batch falls back to the CPU (and says so).
+        #[cfg(not(feature = "ml_gpu"))]
+        for provider in [ExecutionProvider::Gpu, ExecutionProvider::Wgpu] {
+            let options = InferenceOptions::default().with_provider(provider).with_num_threads(2);
+
+            assert_eq!(
+                run_inference_batch(&windows, 5.0, Some(&options)).unwrap(),
+                (vec![expected.clone(), vec![], expected.clone()], Some(ExecutionProvider::Cpu))
+            );
+        }
+
+        // Batches that need no inference report no provider.
+        assert_eq!(run_inference_batch(&[vec![0.0; audio_data.len()]], 5.0, None).unwrap(), (vec![vec![]], None));
+        assert_eq!(run_inference_batch(&[], 5.0, None).unwrap(), (Vec::<Vec<Note>>::new(), None));
+        assert!(run_inference_batch(&[vec![0.0; 4], vec![0.0; 8]], 5.0, None).is_err());
+    }
+
+    #[test]
+    fn test_inference_silence() {
+        assert_eq!(infer(&[], 5.0).unwrap(), vec![]);
+        assert_eq!(infer(&[0.0; 44_100], 1.0).unwrap(), vec![]);
+    }
 }
@@ -3,4 +3,10 @@
 pub mod execute;
 
 pub use execute::infer;
+pub use execute::infer_with_options;
 pub use execute::run_inference;
+pub use execute::run_inference_batch;
+pub use execute::run_inference_batch_with_backend;
+pub use execute::run_inference_with_backend;
+pub use execute::ExecutionProvider;
+pub use execute::InferenceOptions;
@@ -0,0 +1,151 @@
+//! Sends detection results as [OSC](https://opensoundcontrol.stanford.edu/) messages.
+//!
+//! This allows live-performance software (e.g., SuperCollider, TouchDesigner, or Max) to react to the detected notes and
+//! chords.  The messages are:
+//!
+//! * `/kord/notes`: one string argument per note (e.g., `"C4"`), followed by one float argument per note (its frequency).
+//! * `/kord/chord`: the chord name (e.g., `"Am7"`), followed by one string argument per chord tone.
+
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+
+use rosc::{encoder, OscMessage, OscPacket, OscType};
+
+use crate::core::{
+    base::{HasName, Res},
+    chord::{Chord, HasChord},
+    note::Note,
+    pitch::HasFrequency,
+};
+
+// Statics.
+
+/// The OSC address of note messages.
+pub static OSC_NOTES_ADDRESS: &str = "/kord/notes";
+
+/// The OSC address of chord messages.
+pub static OSC_CHORD_ADDRESS: &str = "/kord/chord";
+
+// Struct.
+
+/// An OSC sender, which sends detection results over UDP.
+#[derive(Debug)]
+pub struct OscSender {
+    socket: UdpSocket,
+    target: SocketAddr,
+}
+
+impl OscSender {
+    /// Creates a sender that sends messages to the given target (e.g., `"127.0.0.1:57120"` for SuperCollider).
+    ///
+    /// Broadcast is enabled on the socket, so the target may also be a broadcast address (e.g., `"255.255.255.255:9000"`).
+    pub fn new(target: impl ToSocketAddrs) -> Res<Self> {
+        let target = target.to_socket_addrs()?.next().ok_or_else(|| anyhow::Error::msg("Could not resolve the OSC target address."))?;
+
+        let bind_address = if target.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" };
+        let socket = UdpSocket::bind(bind_address)?;
+        socket.set_broadcast(target.is_ipv4())?;
+
+        Ok(Self { socket, target })
+    }
+
+    /// Returns the address that messages are sent to.
+    pub fn target(&self) -> SocketAddr {
+        self.target
+    }
+
+    /// Sends the given notes as a `/kord/notes` message.
+    pub fn send_notes(&self, notes: &[Note]) -> Res<()> {
+        self.send(&encode_notes(notes)?)
+    }
+
+    /// Sends the given chord as a `/kord/chord` message.
+    pub fn send_chord(&self, chord: &Chord) -> Res<()> {
+        self.send(&encode_chord(chord)?)
+    }
+
+    fn send(&self, packet: &[u8]) -> Res<()> {
+        self.socket.send_to(packet, self.target)?;
+
+        Ok(())
+    }
+}
+
+// Functions.
+
+/// Encodes the given notes as a `/kord/notes` OSC packet.
+pub fn encode_notes(notes: &[Note]) -> Res<Vec<u8>> {
+    let names = notes.iter().map(|n| OscType::String(n.name()));
+    let frequencies = notes.iter().map(|n| OscType::Float(n.frequency()));
+
+    encode(OSC_NOTES_ADDRESS, names.chain(frequencies).collect())
+}
+
+/// Encodes the given chord as a `/kord/chord` OSC packet.
+pub fn encode_chord(chord: &Chord) -> Res<Vec<u8>> {
+    let name = std::iter::once(OscType::String(chord.name()));
+    let tones = chord.chord().into_iter().map(|n| OscType::String(n.name()));
+
+    encode(OSC_CHORD_ADDRESS, name.chain(tones).collect())
+}
+
+// Helpers.
+
+fn encode(address: &str, args: Vec<OscType>) -> Res<Vec<u8>> {
+    let packet = OscPacket::Message(OscMessage { addr: address.to_string(), args });
+
+    encoder::encode(&packet).map_err(|e| anyhow::Error::msg(format!("Could not encode the OSC packet: {e}.")))
+}
+
+// Tests.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{base::Parsable, note::*};
+    use pretty_assertions::assert_eq;
+    use rosc::decoder;
+
+    #[test]
+    fn test_encode_chord() {
+        let packet = encode_chord(&Chord::parse("Am7").unwrap()).unwrap();
+
+        // OSC packets are always 4-byte aligned.
+        assert_eq!(packet.len() % 4, 0);
+
+        let (rest, decoded) = decoder::decode_udp(&packet).unwrap();
+
+        assert!(rest.is_empty());
+        assert_eq!(
+            decoded,
+            OscPacket::Message(OscMessage {
+                addr: "/kord/chord".to_string(),
+                args: ["Am7", "A4", "C5", "E5", "G5"].iter().map(|s| OscType::String(s.to_string())).collect(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_send_notes() {
+        let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let sender = OscSender::new(receiver.local_addr().unwrap()).unwrap();
+
+        sender.send_notes(&[C, E]).unwrap();
+
+        let mut buffer = [0u8; 1024];
+        let size = receiver.recv(&mut buffer).unwrap();
+        let (_, decoded) = decoder::decode_udp(&buffer[..size]).unwrap();
+
+        assert_eq!(
+            decoded,
+            OscPacket::Message(OscMessage {
+                addr: "/kord/notes".to_string(),
+                args: vec![
+                    OscType::String("C4".to_string()),
+                    OscType::String("E4".to_string()),
+                    OscType::Float(C.frequency()),
+                    OscType::Float(E.frequency())
+                ],
+            })
+        );
+    }
+}
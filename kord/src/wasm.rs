@@ -6,8 +6,8 @@ use js_sys::{Array, Object, Reflect};
 use wasm_bindgen::{convert::RefFromWasmAbi, prelude::*};
 
 use crate::core::{
-    base::{HasDescription, HasName, HasPreciseName, HasStaticName, Parsable, PlaybackHandle, Res},
-    chord::{Chord, Chordable, HasChord, HasExtensions, HasInversion, HasIsCrunchy, HasModifiers, HasRoot, HasScale, HasSlash},
+    base::{HasDescription, HasName, HasPreciseName, HasStaticName, Parsable, Res},
+    chord::{Chord, Chordable, HasAddedTones, HasChord, HasExtensions, HasInversion, HasIsCrunchy, HasModifiers, HasRoot, HasScale, HasSlash},
     interval::Interval,
     named_pitch::HasNamedPitch,
     note::{HasPrimaryHarmonicSeries, Note},
@@ -63,6 +63,17 @@ impl KordNote {
         Ok(notes.into_js_array())
     }
 
+    /// Returns [`Note`]s from the last `length_in_seconds` of raw samples (e.g., from the Web Audio API) at the given sample rate.
+    #[cfg(feature = "analyze_base")]
+    #[wasm_bindgen(js_name = fromSamples)]
+    pub fn from_samples(data: &[f32], sample_rate: u32, length_in_seconds: f32) -> JsRes<Array> {
+        use crate::analyze::base::analyze_samples;
+
+        let notes = analyze_samples(data, sample_rate, length_in_seconds).to_js_error()?.into_iter().map(KordNote::from);
+
+        Ok(notes.into_js_array())
+    }
+
     /// Returns [`Note`]s from audio data using the ML inference algorithm.
     #[cfg(all(feature = "ml_infer", feature = "analyze_base"))]
     #[wasm_bindgen(js_name = fromAudioMl)]
@@ -286,6 +297,12 @@ impl KordChord {
         self.inner.extensions().iter().map(|e| e.static_name()).into_js_array()
     }
 
+    /// Returns the [`Chord`]'s added tones.
+    #[wasm_bindgen(js_name = addedTones)]
+    pub fn added_tones(&self) -> Array {
+        self.inner.added_tones().iter().map(|e| e.static_name()).into_js_array()
+    }
+
     /// Returns a new [`Chord`] with the inversion set to the provided value.
     #[wasm_bindgen(js_name = withInversion)]
     pub fn with_inversion(&self, inversion: u8) -> Self {
@@ -350,9 +367,10 @@ impl KordChord {
 /// A handle to a [`Chord`] playback.
 ///
 /// Should be dropped to stop the playback, or after playback is finished.
+#[cfg(feature = "audio")]
 #[wasm_bindgen]
 pub struct KordPlaybackHandle {
-    _inner: PlaybackHandle,
+    _inner: crate::core::base::PlaybackHandle,
 }
 
 // The modifiers.